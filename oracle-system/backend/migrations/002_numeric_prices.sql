@@ -0,0 +1,4 @@
+-- Store prices exactly instead of rounding through DOUBLE PRECISION
+ALTER TABLE price_history
+    ALTER COLUMN price TYPE NUMERIC USING price::NUMERIC,
+    ALTER COLUMN confidence TYPE NUMERIC USING confidence::NUMERIC;
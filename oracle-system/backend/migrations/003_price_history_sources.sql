@@ -0,0 +1,15 @@
+-- Per-source breakdown of each consensus price: contributing sources and rejected ones with the reason
+CREATE TABLE IF NOT EXISTS price_history_sources (
+    id SERIAL PRIMARY KEY,
+    price_history_id INTEGER NOT NULL REFERENCES price_history(id) ON DELETE CASCADE,
+    source VARCHAR(50) NOT NULL,
+    used BOOLEAN NOT NULL,
+    price NUMERIC,
+    confidence NUMERIC,
+    age_secs BIGINT,
+    deviation_bps BIGINT,
+    rejection_reason VARCHAR(20),
+    rejection_detail TEXT
+);
+
+CREATE INDEX idx_price_history_sources_history_id ON price_history_sources(price_history_id);
@@ -0,0 +1,10 @@
+-- Symbol changes made through the admin API, layered over the symbol config file on startup
+CREATE TABLE IF NOT EXISTS symbol_overrides (
+    symbol VARCHAR(20) PRIMARY KEY,
+    -- Replaces the file's config for the symbol when set
+    config JSONB,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Hides a symbol the file still defines
+    removed BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'UTC')
+);
@@ -0,0 +1,17 @@
+-- Backend consensus prices checked against what the oracle program returns in simulation
+CREATE TABLE IF NOT EXISTS reconciliation_history (
+    id SERIAL PRIMARY KEY,
+    symbol VARCHAR(20) NOT NULL,
+    backend_price NUMERIC NOT NULL,
+    -- get_pyth_price result, or the program error that rejected it
+    onchain_pyth_price NUMERIC,
+    pyth_divergence_bps BIGINT,
+    pyth_error TEXT,
+    -- validate_price_consensus over the backend's contributing sources
+    onchain_consensus_price NUMERIC,
+    consensus_divergence_bps BIGINT,
+    consensus_error TEXT,
+    timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_reconciliation_history_symbol_timestamp ON reconciliation_history(symbol, timestamp DESC);
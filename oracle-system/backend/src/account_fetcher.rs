@@ -0,0 +1,58 @@
+use solana_client::rpc_request::MAX_MULTIPLE_ACCOUNTS;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use futures::future::try_join_all;
+use anyhow::Result;
+use crate::rpc_pool::RpcPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// A feed account together with the slot of the RPC context it was read at
+#[derive(Clone)]
+pub struct FeedAccount {
+    pub account: Account,
+    pub slot: u64,
+}
+
+// Fetches every feed account needed for a tick in as few RPC round trips as possible
+pub struct AccountFetcher {
+    rpc_pool: Arc<RpcPool>,
+}
+
+impl AccountFetcher {
+    pub fn new(rpc_pool: Arc<RpcPool>) -> Self {
+        Self {
+            rpc_pool,
+        }
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.rpc_pool.commitment()
+    }
+
+    pub async fn fetch_accounts(&self, pubkeys: &[Pubkey]) -> Result<HashMap<Pubkey, FeedAccount>> {
+        // getMultipleAccounts rejects requests above MAX_MULTIPLE_ACCOUNTS keys, so chunks go out concurrently
+        let commitment = self.commitment();
+        let chunks = pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS).map(|chunk| async move {
+            let response = self
+                .rpc_pool
+                .execute(|client| async move { client.get_multiple_accounts_with_commitment(chunk, commitment).await })
+                .await?;
+            let slot = response.context.slot;
+            Ok::<_, anyhow::Error>(chunk.iter().copied().zip(response.value).map(move |(key, account)| {
+                (key, account.map(|account| FeedAccount { account, slot }))
+            }))
+        });
+
+        // Missing accounts come back as None; callers treat them as fetch errors
+        let accounts = try_join_all(chunks)
+            .await?
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, account)| account.map(|account| (key, account)))
+            .collect();
+
+        Ok(accounts)
+    }
+}
@@ -0,0 +1,112 @@
+use crate::account_fetcher::FeedAccount;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_account_decoder::UiAccountEncoding;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use futures::stream::{select_all, StreamExt};
+use anyhow::{Result, anyhow};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use log::{info, warn, error};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+pub struct AccountUpdate {
+    pub pubkey: Pubkey,
+    pub account: FeedAccount,
+}
+
+// Streams feed account changes over accountSubscribe instead of polling
+pub struct AccountSubscriber {
+    ws_url: String,
+    commitment: CommitmentConfig,
+    // A socket that stays open but stops delivering notifications is treated as dropped after this long
+    idle_timeout: Duration,
+    connected: Arc<AtomicBool>,
+}
+
+impl AccountSubscriber {
+    pub fn new(ws_url: String, commitment: CommitmentConfig, idle_timeout: Duration) -> Self {
+        Self {
+            ws_url,
+            commitment,
+            idle_timeout,
+            connected: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // Shared flag the manager reads to decide whether it must fall back to polling
+    pub fn connected(&self) -> Arc<AtomicBool> {
+        self.connected.clone()
+    }
+
+    // Runs until the receiving side is dropped, resubscribing with backoff whenever the socket drops
+    pub async fn run(self: Arc<Self>, pubkeys: Vec<Pubkey>, updates: mpsc::Sender<AccountUpdate>) {
+        let mut backoff = INITIAL_BACKOFF;
+
+        while !updates.is_closed() {
+            match self.subscribe_once(&pubkeys, &updates).await {
+                Ok(()) => {
+                    warn!("Account subscription stream to {} ended, resubscribing", self.ws_url);
+                    backoff = INITIAL_BACKOFF;
+                }
+                Err(e) => {
+                    error!("Account subscription to {} failed: {}", self.ws_url, e);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+
+            self.connected.store(false, Ordering::SeqCst);
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    async fn subscribe_once(&self, pubkeys: &[Pubkey], updates: &mpsc::Sender<AccountUpdate>) -> Result<()> {
+        let client = PubsubClient::new(&self.ws_url).await?;
+
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..RpcAccountInfoConfig::default()
+        };
+
+        let mut streams = Vec::with_capacity(pubkeys.len());
+        for pubkey in pubkeys {
+            let (stream, _unsubscribe) = client.account_subscribe(pubkey, Some(config.clone())).await?;
+            let pubkey = *pubkey;
+            streams.push(stream.map(move |response| (pubkey, response.context.slot, response.value)));
+        }
+
+        info!("Subscribed to {} feed accounts via {}", pubkeys.len(), self.ws_url);
+        self.connected.store(true, Ordering::SeqCst);
+
+        let mut merged = select_all(streams);
+        loop {
+            // Symbols keep polling while disconnected, so a silent socket must not leave `connected` set
+            let (pubkey, slot, ui_account) = match tokio::time::timeout(self.idle_timeout, merged.next()).await {
+                Ok(Some(notification)) => notification,
+                Ok(None) => break,
+                Err(_) => return Err(anyhow!("No account notification within {:?}", self.idle_timeout)),
+            };
+
+            let account = ui_account
+                .decode::<Account>()
+                .ok_or_else(|| anyhow!("Failed to decode account update for {}", pubkey))?;
+
+            let update = AccountUpdate {
+                pubkey,
+                account: FeedAccount { account, slot },
+            };
+            if updates.send(update).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+use crate::alerting::{Alert, AlertState, Severity};
+use async_trait::async_trait;
+use anyhow::{Result, anyhow, bail};
+use std::time::Duration;
+use log::{info, warn};
+
+// Default PagerDuty Events API v2 endpoint; overridable to test against a local stub
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+const SINK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Somewhere alerts are delivered; each firing and each recovery is sent once
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    fn name(&self) -> &str;
+    async fn send(&self, alert: &Alert) -> Result<()>;
+}
+
+// Parses ALERT_SINKS entries: "log", "webhook=<url>", "slack=<url>" or "pagerduty=<routing key>"
+pub fn parse_sinks(spec: &str, pagerduty_url: &str) -> Result<Vec<Box<dyn AlertSink>>> {
+    let http = reqwest::Client::builder().timeout(SINK_TIMEOUT).build()?;
+
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let sink: Box<dyn AlertSink> = match entry.split_once('=') {
+                None if entry == "log" => Box::new(LogSink),
+                Some(("webhook", url)) => Box::new(WebhookSink { http: http.clone(), url: url.to_string() }),
+                Some(("slack", url)) => Box::new(SlackSink { http: http.clone(), url: url.to_string() }),
+                Some(("pagerduty", routing_key)) => Box::new(PagerDutySink {
+                    http: http.clone(),
+                    url: pagerduty_url.to_string(),
+                    routing_key: routing_key.to_string(),
+                }),
+                _ => bail!("Unknown alert sink {:?}", entry),
+            };
+            Ok(sink)
+        })
+        .collect()
+}
+
+pub struct LogSink;
+
+#[async_trait]
+impl AlertSink for LogSink {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        match alert.state {
+            AlertState::Firing => warn!("ALERT firing [{}]: {}", alert.key, alert.summary),
+            AlertState::Resolved => info!("ALERT resolved [{}]: {}", alert.key, alert.summary),
+        }
+        Ok(())
+    }
+}
+
+// POSTs the alert as JSON, unchanged
+pub struct WebhookSink {
+    http: reqwest::Client,
+    url: String,
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        post(&self.http, &self.url, &serde_json::to_value(alert)?).await
+    }
+}
+
+// Slack incoming-webhook payload; also accepted by Mattermost, Rocket.Chat and similar
+pub struct SlackSink {
+    http: reqwest::Client,
+    url: String,
+}
+
+#[async_trait]
+impl AlertSink for SlackSink {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let state = match alert.state {
+            AlertState::Firing => "FIRING",
+            AlertState::Resolved => "RESOLVED",
+        };
+        let body = serde_json::json!({
+            "text": format!("[{}] {} ({:?}): {}", state, alert.rule, alert.severity, alert.summary),
+        });
+        post(&self.http, &self.url, &body).await
+    }
+}
+
+// PagerDuty Events API v2; the alert key is the dedup key, so a recovery resolves the incident it opened
+pub struct PagerDutySink {
+    http: reqwest::Client,
+    url: String,
+    routing_key: String,
+}
+
+#[async_trait]
+impl AlertSink for PagerDutySink {
+    fn name(&self) -> &str {
+        "pagerduty"
+    }
+
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let body = match alert.state {
+            AlertState::Firing => serde_json::json!({
+                "routing_key": self.routing_key,
+                "event_action": "trigger",
+                "dedup_key": alert.key,
+                "payload": {
+                    "summary": alert.summary,
+                    "source": "oracle-backend",
+                    "severity": match alert.severity {
+                        Severity::Warning => "warning",
+                        Severity::Critical => "critical",
+                    },
+                    "component": alert.symbol,
+                    "class": alert.rule,
+                    "custom_details": alert,
+                },
+            }),
+            AlertState::Resolved => serde_json::json!({
+                "routing_key": self.routing_key,
+                "event_action": "resolve",
+                "dedup_key": alert.key,
+            }),
+        };
+        post(&self.http, &self.url, &body).await
+    }
+}
+
+async fn post(http: &reqwest::Client, url: &str, body: &serde_json::Value) -> Result<()> {
+    let response = http.post(url).json(body).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("{} returned {}", url, response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    // Accepts `requests` HTTP requests on a local port, answers each with 200 and returns their JSON bodies
+    async fn stub(requests: usize) -> (String, JoinHandle<Vec<serde_json::Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let bodies = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for _ in 0..requests {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut chunk = [0u8; 4096];
+                let body = loop {
+                    let read = socket.read(&mut chunk).await.unwrap();
+                    assert!(read > 0, "connection closed mid-request");
+                    request.extend_from_slice(&chunk[..read]);
+
+                    let text = String::from_utf8_lossy(&request);
+                    let Some(header_end) = text.find("\r\n\r\n") else { continue };
+                    let length: usize = text[..header_end]
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + length {
+                        break request[header_end + 4..header_end + 4 + length].to_vec();
+                    }
+                };
+                bodies.push(serde_json::from_slice(&body).unwrap());
+                socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await.unwrap();
+            }
+            bodies
+        });
+
+        (url, bodies)
+    }
+
+    fn alert(state: AlertState) -> Alert {
+        Alert {
+            key: "SOL:quorum_lost".to_string(),
+            rule: "quorum_lost",
+            symbol: "SOL".to_string(),
+            source: None,
+            severity: Severity::Critical,
+            state,
+            summary: "SOL: Consensus withheld: 0 of 1 sources agree".to_string(),
+            started_at: 1_700_000_000,
+            timestamp: 1_700_000_030,
+        }
+    }
+
+    fn sink(spec: &str, pagerduty_url: &str) -> Box<dyn AlertSink> {
+        parse_sinks(spec, pagerduty_url).unwrap().pop().unwrap()
+    }
+
+    #[tokio::test]
+    async fn slack_posts_a_text_message() {
+        let (url, bodies) = stub(2).await;
+        let slack = sink(&format!("slack={}", url), PAGERDUTY_EVENTS_URL);
+
+        slack.send(&alert(AlertState::Firing)).await.unwrap();
+        slack.send(&alert(AlertState::Resolved)).await.unwrap();
+
+        let bodies = bodies.await.unwrap();
+        assert_eq!(
+            bodies[0],
+            serde_json::json!({ "text": "[FIRING] quorum_lost (Critical): SOL: Consensus withheld: 0 of 1 sources agree" })
+        );
+        assert_eq!(
+            bodies[1],
+            serde_json::json!({ "text": "[RESOLVED] quorum_lost (Critical): SOL: Consensus withheld: 0 of 1 sources agree" })
+        );
+    }
+
+    #[tokio::test]
+    async fn pagerduty_resolves_with_the_trigger_dedup_key() {
+        let (url, bodies) = stub(2).await;
+        let pagerduty = sink("pagerduty=routing-key", &url);
+
+        pagerduty.send(&alert(AlertState::Firing)).await.unwrap();
+        pagerduty.send(&alert(AlertState::Resolved)).await.unwrap();
+
+        let bodies = bodies.await.unwrap();
+        let (trigger, resolve) = (&bodies[0], &bodies[1]);
+        assert_eq!(trigger["routing_key"], "routing-key");
+        assert_eq!(trigger["event_action"], "trigger");
+        assert_eq!(trigger["dedup_key"], "SOL:quorum_lost");
+        assert_eq!(trigger["payload"]["summary"], "SOL: Consensus withheld: 0 of 1 sources agree");
+        assert_eq!(trigger["payload"]["source"], "oracle-backend");
+        assert_eq!(trigger["payload"]["severity"], "critical");
+        assert_eq!(trigger["payload"]["component"], "SOL");
+        assert_eq!(trigger["payload"]["class"], "quorum_lost");
+        assert_eq!(trigger["payload"]["custom_details"]["state"], "firing");
+
+        assert_eq!(
+            *resolve,
+            serde_json::json!({ "routing_key": "routing-key", "event_action": "resolve", "dedup_key": "SOL:quorum_lost" })
+        );
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_an_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut chunk = [0u8; 4096];
+            let _ = socket.read(&mut chunk).await;
+            let _ = socket.write_all(b"HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await;
+        });
+
+        assert!(sink(&format!("webhook={}", url), PAGERDUTY_EVENTS_URL).send(&alert(AlertState::Firing)).await.is_err());
+    }
+
+    #[test]
+    fn unknown_sinks_are_rejected() {
+        assert_eq!(parse_sinks("log, slack=http://localhost/hook", PAGERDUTY_EVENTS_URL).unwrap().len(), 2);
+        assert!(parse_sinks("email=ops@example.com", PAGERDUTY_EVENTS_URL).is_err());
+    }
+}
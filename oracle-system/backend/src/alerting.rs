@@ -0,0 +1,545 @@
+use crate::alert_sinks::AlertSink;
+use crate::oracle_manager::OracleManager;
+use crate::price_aggregator::{AggregationError, ConsensusPrice, RejectionReason};
+use crate::symbol_config::KNOWN_SOURCES;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use log::{error, warn};
+
+// Failed deliveries are retried after 1s, doubling up to 5 minutes, and dropped after 10 attempts
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(300);
+const MAX_DELIVERY_ATTEMPTS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    // "<symbol>:<rule>[:<source>]"; stable for the life of the condition, so sinks can deduplicate on it
+    pub key: String,
+    pub rule: &'static str,
+    pub symbol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub severity: Severity,
+    pub state: AlertState,
+    pub summary: String,
+    // When the condition was first detected
+    pub started_at: i64,
+    pub timestamp: i64,
+}
+
+// Thresholds for the built-in rules; quorum_lost fires on the first round without quorum
+#[derive(Debug, Clone)]
+pub struct AlertRules {
+    // A source has produced no readable price for this long
+    pub source_down_secs: i64,
+    // A source's latest price is this far from the median
+    pub deviation_bps: u64,
+    // The symbol has produced no consensus price for this long
+    pub stale_secs: i64,
+}
+
+#[derive(Default)]
+struct SourceState {
+    last_read: Option<i64>,
+    deviation_bps: Option<u64>,
+}
+
+struct SymbolState {
+    // Stands in for the last success before the first one, so a symbol that never worked still alerts
+    first_seen: i64,
+    last_consensus: Option<i64>,
+    quorum_lost: Option<String>,
+    // Set by the reconciliation monitor while the oracle program disagrees with the backend
+    divergence: Option<String>,
+    sources: HashMap<String, SourceState>,
+}
+
+impl SymbolState {
+    fn new(now: i64) -> Self {
+        Self {
+            first_seen: now,
+            last_consensus: None,
+            quorum_lost: None,
+            divergence: None,
+            sources: HashMap::new(),
+        }
+    }
+}
+
+// Condition currently holding for a symbol, before it is matched against the active alerts
+struct Condition {
+    rule: &'static str,
+    source: Option<String>,
+    severity: Severity,
+    summary: String,
+}
+
+// An alert a sink failed to accept, waiting for its next attempt
+struct PendingDelivery {
+    alert: Alert,
+    // Index into the engine's sinks
+    sink: usize,
+    attempts: u32,
+    retry_at: Instant,
+}
+
+// Tracks each symbol's rounds and turns rule breaches into alerts, sending each firing and recovery once
+pub struct AlertEngine {
+    rules: AlertRules,
+    sinks: Vec<Box<dyn AlertSink>>,
+    symbols: Mutex<HashMap<String, SymbolState>>,
+    active: Mutex<HashMap<String, Alert>>,
+    pending: Mutex<Vec<PendingDelivery>>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: AlertRules, sinks: Vec<Box<dyn AlertSink>>) -> Self {
+        Self {
+            rules,
+            sinks,
+            symbols: Mutex::new(HashMap::new()),
+            active: Mutex::new(HashMap::new()),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Sources that produced a readable price this round
+    pub fn observe_sources(&self, symbol: &str, read: &[&str]) {
+        let now = chrono::Utc::now().timestamp();
+        let mut symbols = self.symbols.lock().unwrap();
+        let state = symbols.entry(symbol.to_string()).or_insert_with(|| SymbolState::new(now));
+        for source in read {
+            state.sources.entry(source.to_string()).or_default().last_read = Some(now);
+        }
+    }
+
+    pub fn observe_round(&self, symbol: &str, result: &Result<ConsensusPrice, AggregationError>) {
+        let now = chrono::Utc::now().timestamp();
+        let mut symbols = self.symbols.lock().unwrap();
+        let state = symbols.entry(symbol.to_string()).or_insert_with(|| SymbolState::new(now));
+
+        // Deviation reflects this round only, so a source that stops being compared doesn't keep alerting
+        if matches!(result, Ok(_) | Err(AggregationError::InsufficientQuorum(_))) {
+            state.sources.values_mut().for_each(|source| source.deviation_bps = None);
+        }
+
+        let rejected = match result {
+            Ok(consensus_price) => {
+                state.last_consensus = Some(now);
+                state.quorum_lost = None;
+                for source in &consensus_price.sources {
+                    state.sources.entry(source.source.clone()).or_default().deviation_bps = Some(source.deviation_bps);
+                }
+                consensus_price.rejected.as_slice()
+            }
+            Err(AggregationError::InsufficientQuorum(failure)) => {
+                state.quorum_lost = Some(format!(
+                    "{} of {} sources agree, missing required {:?}",
+                    failure.sources_used, failure.min_sources, failure.missing_required
+                ));
+                failure.rejected.as_slice()
+            }
+            Err(AggregationError::Unhealthy(report)) => {
+                state.quorum_lost = Some(report.reason.clone());
+                &[]
+            }
+            Err(_) => &[],
+        };
+
+        for rejection in rejected {
+            if let RejectionReason::Deviation { deviation_bps } | RejectionReason::IntervalDisjoint { deviation_bps } =
+                rejection.reason
+            {
+                state.sources.entry(rejection.source.clone()).or_default().deviation_bps = Some(deviation_bps);
+            }
+        }
+    }
+
+    // Latest reconciliation of a symbol against the oracle program; None once they agree again
+    pub fn observe_divergence(&self, symbol: &str, divergence: Option<String>) {
+        let now = chrono::Utc::now().timestamp();
+        let mut symbols = self.symbols.lock().unwrap();
+        symbols.entry(symbol.to_string()).or_insert_with(|| SymbolState::new(now)).divergence = divergence;
+    }
+
+    // Evaluates the rules once per interval. Only the leader sends, so replicas don't page twice.
+    pub async fn run(self: Arc<Self>, manager: Arc<OracleManager>, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => return,
+            }
+
+            if !manager.is_leader() {
+                continue;
+            }
+
+            let symbols: Vec<String> = manager.configs().into_iter().map(|config| config.symbol).collect();
+            let outgoing = self.evaluate(&symbols);
+            self.deliver(&outgoing).await;
+        }
+    }
+
+    // Alerts to send: conditions that just started firing, and active alerts whose condition cleared
+    fn evaluate(&self, configured: &[String]) -> Vec<Alert> {
+        let now = chrono::Utc::now().timestamp();
+        let mut symbols = self.symbols.lock().unwrap();
+        // Removed symbols stop being tracked; their active alerts resolve below
+        symbols.retain(|symbol, _| configured.contains(symbol));
+
+        let mut current = HashMap::new();
+        for symbol in configured {
+            let state = symbols.entry(symbol.clone()).or_insert_with(|| SymbolState::new(now));
+            for condition in self.conditions(state, now) {
+                let key = match &condition.source {
+                    Some(source) => format!("{}:{}:{}", symbol, condition.rule, source),
+                    None => format!("{}:{}", symbol, condition.rule),
+                };
+                current.insert(key, (symbol.clone(), condition));
+            }
+        }
+
+        let mut active = self.active.lock().unwrap();
+        let mut outgoing = Vec::new();
+
+        active.retain(|key, alert| {
+            if current.contains_key(key) {
+                return true;
+            }
+            outgoing.push(Alert {
+                state: AlertState::Resolved,
+                timestamp: now,
+                ..alert.clone()
+            });
+            false
+        });
+
+        for (key, (symbol, condition)) in current {
+            if active.contains_key(&key) {
+                continue;
+            }
+            let alert = Alert {
+                key: key.clone(),
+                rule: condition.rule,
+                summary: format!("{}: {}", symbol, condition.summary),
+                symbol,
+                source: condition.source,
+                severity: condition.severity,
+                state: AlertState::Firing,
+                started_at: now,
+                timestamp: now,
+            };
+            active.insert(key, alert.clone());
+            outgoing.push(alert);
+        }
+
+        outgoing
+    }
+
+    fn conditions(&self, state: &SymbolState, now: i64) -> Vec<Condition> {
+        let mut conditions = Vec::new();
+
+        for source in KNOWN_SOURCES {
+            let source_state = state.sources.get(source);
+            let down_secs = now - source_state.and_then(|s| s.last_read).unwrap_or(state.first_seen);
+            if down_secs > self.rules.source_down_secs {
+                conditions.push(Condition {
+                    rule: "source_down",
+                    source: Some(source.to_string()),
+                    severity: Severity::Warning,
+                    summary: format!("{} has produced no readable price for {}s", source, down_secs),
+                });
+            }
+
+            if let Some(bps) = source_state.and_then(|s| s.deviation_bps).filter(|bps| *bps > self.rules.deviation_bps) {
+                conditions.push(Condition {
+                    rule: "deviation",
+                    source: Some(source.to_string()),
+                    severity: Severity::Warning,
+                    summary: format!("{} is {} bps from the median (limit {})", source, bps, self.rules.deviation_bps),
+                });
+            }
+        }
+
+        let stale_secs = now - state.last_consensus.unwrap_or(state.first_seen);
+        if stale_secs > self.rules.stale_secs {
+            conditions.push(Condition {
+                rule: "stale",
+                source: None,
+                severity: Severity::Critical,
+                summary: format!("No consensus price for {}s", stale_secs),
+            });
+        }
+
+        if let Some(reason) = &state.quorum_lost {
+            conditions.push(Condition {
+                rule: "quorum_lost",
+                source: None,
+                severity: Severity::Critical,
+                summary: format!("Consensus withheld: {}", reason),
+            });
+        }
+
+        if let Some(divergence) = &state.divergence {
+            conditions.push(Condition {
+                rule: "onchain_divergence",
+                source: None,
+                severity: Severity::Critical,
+                summary: format!("Oracle program disagrees with the backend: {}", divergence),
+            });
+        }
+
+        conditions
+    }
+
+    // Sends new alerts to every sink, along with earlier failed deliveries whose backoff has passed.
+    // Failures stay pending per sink, so a sink that is down doesn't make the others resend.
+    async fn deliver(&self, alerts: &[Alert]) {
+        let now = Instant::now();
+        let mut due = {
+            let mut pending = self.pending.lock().unwrap();
+            // A newer state of the same alert replaces an undelivered older one
+            pending.retain(|delivery| !alerts.iter().any(|alert| alert.key == delivery.alert.key));
+            let (due, waiting): (Vec<_>, Vec<_>) =
+                std::mem::take(&mut *pending).into_iter().partition(|delivery| delivery.retry_at <= now);
+            *pending = waiting;
+            due
+        };
+        for alert in alerts {
+            due.extend((0..self.sinks.len()).map(|sink| PendingDelivery { alert: alert.clone(), sink, attempts: 0, retry_at: now }));
+        }
+
+        let sends = due.into_iter().map(|delivery| async move {
+            let result = self.sinks[delivery.sink].send(&delivery.alert).await;
+            (delivery, result)
+        });
+        for (mut delivery, result) in futures::future::join_all(sends).await {
+            let Err(e) = result else {
+                continue;
+            };
+            let sink = self.sinks[delivery.sink].name();
+            delivery.attempts += 1;
+            if delivery.attempts >= MAX_DELIVERY_ATTEMPTS {
+                error!("Giving up on alert {} to {} after {} attempts: {}", delivery.alert.key, sink, delivery.attempts, e);
+                continue;
+            }
+
+            let backoff = RETRY_BACKOFF.saturating_mul(1 << (delivery.attempts - 1)).min(MAX_RETRY_BACKOFF);
+            warn!("Failed to deliver alert {} to {}, retrying in {:?}: {}", delivery.alert.key, sink, backoff, e);
+            delivery.retry_at = Instant::now() + backoff;
+            self.pending.lock().unwrap().push(delivery);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_aggregator::{AggregationStrategy, QuorumFailure};
+    use anyhow::{bail, Result};
+    use async_trait::async_trait;
+    use oracle_core::FixedPrice;
+
+    // Rejects the first `failures` sends and records the rest
+    struct FlakySink {
+        failures: Mutex<u32>,
+        sent: Arc<Mutex<Vec<Alert>>>,
+    }
+
+    #[async_trait]
+    impl AlertSink for FlakySink {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn send(&self, alert: &Alert) -> Result<()> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                bail!("sink unavailable");
+            }
+            self.sent.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    fn flaky_engine(failures: u32) -> (AlertEngine, Arc<Mutex<Vec<Alert>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = FlakySink { failures: Mutex::new(failures), sent: sent.clone() };
+        let rules = AlertRules { source_down_secs: 3_600, deviation_bps: 10_000, stale_secs: 60 };
+        (AlertEngine::new(rules, vec![Box::new(sink)]), sent)
+    }
+
+    // Lets pending deliveries through without waiting out the backoff
+    fn expire_backoff(engine: &AlertEngine) {
+        engine.pending.lock().unwrap().iter_mut().for_each(|delivery| delivery.retry_at = Instant::now());
+    }
+
+    fn engine() -> AlertEngine {
+        // Source rules are set out of reach so only the symbol-level rules fire
+        let rules = AlertRules { source_down_secs: 3_600, deviation_bps: 10_000, stale_secs: 60 };
+        AlertEngine::new(rules, Vec::new())
+    }
+
+    fn symbols() -> Vec<String> {
+        vec!["SOL".to_string()]
+    }
+
+    fn consensus() -> Result<ConsensusPrice, AggregationError> {
+        Ok(ConsensusPrice {
+            price: FixedPrice::new(14_235, -2),
+            confidence: FixedPrice::new(5, -2),
+            timestamp: chrono::Utc::now().timestamp(),
+            sources_used: 1,
+            strategy: AggregationStrategy::Mean,
+            sources: Vec::new(),
+            rejected: Vec::new(),
+        })
+    }
+
+    fn quorum_lost() -> Result<ConsensusPrice, AggregationError> {
+        Err(AggregationError::InsufficientQuorum(QuorumFailure {
+            sources_used: 0,
+            min_sources: 1,
+            missing_required: vec!["Pyth".to_string()],
+            rejected: Vec::new(),
+            timestamp: chrono::Utc::now().timestamp(),
+        }))
+    }
+
+    #[test]
+    fn stale_fires_once_and_resolves_on_the_next_consensus() {
+        let engine = engine();
+        assert!(engine.evaluate(&symbols()).is_empty());
+
+        let first_seen = chrono::Utc::now().timestamp() - 120;
+        engine.symbols.lock().unwrap().get_mut("SOL").unwrap().first_seen = first_seen;
+        let fired = engine.evaluate(&symbols());
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].key, "SOL:stale");
+        assert_eq!(fired[0].state, AlertState::Firing);
+        assert_eq!(fired[0].severity, Severity::Critical);
+
+        // Still stale: already active, so nothing is re-sent
+        assert!(engine.evaluate(&symbols()).is_empty());
+
+        engine.observe_round("SOL", &consensus());
+        let resolved = engine.evaluate(&symbols());
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].key, "SOL:stale");
+        assert_eq!(resolved[0].state, AlertState::Resolved);
+        assert_eq!(resolved[0].started_at, fired[0].started_at);
+        assert!(engine.evaluate(&symbols()).is_empty());
+    }
+
+    #[test]
+    fn quorum_lost_fires_once_and_resolves_on_the_next_consensus() {
+        let engine = engine();
+
+        engine.observe_round("SOL", &quorum_lost());
+        let fired = engine.evaluate(&symbols());
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].key, "SOL:quorum_lost");
+        assert_eq!(fired[0].state, AlertState::Firing);
+        assert!(fired[0].summary.starts_with("SOL: Consensus withheld: 0 of 1 sources agree"));
+
+        engine.observe_round("SOL", &quorum_lost());
+        assert!(engine.evaluate(&symbols()).is_empty());
+
+        engine.observe_round("SOL", &consensus());
+        let resolved = engine.evaluate(&symbols());
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].key, "SOL:quorum_lost");
+        assert_eq!(resolved[0].state, AlertState::Resolved);
+    }
+
+    #[test]
+    fn removing_a_symbol_resolves_its_alerts() {
+        let engine = engine();
+
+        engine.observe_round("SOL", &quorum_lost());
+        assert_eq!(engine.evaluate(&symbols()).len(), 1);
+
+        let resolved = engine.evaluate(&[]);
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].state, AlertState::Resolved);
+    }
+
+    #[test]
+    fn onchain_divergence_fires_once_and_resolves_when_reconciled() {
+        let engine = engine();
+        engine.observe_round("SOL", &consensus());
+
+        engine.observe_divergence("SOL", Some("consensus 120 bps from the backend".to_string()));
+        let fired = engine.evaluate(&symbols());
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].key, "SOL:onchain_divergence");
+        assert_eq!(fired[0].severity, Severity::Critical);
+        assert!(fired[0].summary.ends_with("consensus 120 bps from the backend"));
+
+        engine.observe_divergence("SOL", Some("consensus 130 bps from the backend".to_string()));
+        assert!(engine.evaluate(&symbols()).is_empty());
+
+        engine.observe_divergence("SOL", None);
+        let resolved = engine.evaluate(&symbols());
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].key, "SOL:onchain_divergence");
+        assert_eq!(resolved[0].state, AlertState::Resolved);
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_after_its_backoff() {
+        let (engine, sent) = flaky_engine(1);
+
+        engine.observe_round("SOL", &quorum_lost());
+        engine.deliver(&engine.evaluate(&symbols())).await;
+        assert!(sent.lock().unwrap().is_empty());
+        assert_eq!(engine.pending.lock().unwrap()[0].attempts, 1);
+
+        // Still inside the backoff: nothing is resent
+        engine.deliver(&engine.evaluate(&symbols())).await;
+        assert!(sent.lock().unwrap().is_empty());
+
+        expire_backoff(&engine);
+        engine.deliver(&engine.evaluate(&symbols())).await;
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].key, "SOL:quorum_lost");
+        assert_eq!(sent[0].state, AlertState::Firing);
+        assert!(engine.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn newer_state_replaces_an_undelivered_alert() {
+        let (engine, sent) = flaky_engine(1);
+
+        engine.observe_round("SOL", &quorum_lost());
+        engine.deliver(&engine.evaluate(&symbols())).await;
+
+        engine.observe_round("SOL", &consensus());
+        engine.deliver(&engine.evaluate(&symbols())).await;
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].state, AlertState::Resolved);
+        assert!(engine.pending.lock().unwrap().is_empty());
+    }
+}
@@ -1,27 +1,241 @@
 use warp::Filter;
+use warp::http::StatusCode;
+use std::future::Future;
 use std::sync::Arc;
 use crate::database::Database;
+use crate::health::{HealthChecker, HealthStatus};
+use crate::metrics::Metrics;
+use crate::rpc_pool::RpcPool;
+use crate::symbol_config::{RegistryError, SymbolRegistry, SymbolSpec};
 
-pub async fn start_api_server(database: Arc<Database>) {
+// Admin request without a valid bearer token
+#[derive(Debug)]
+struct Unauthorized;
+
+impl warp::reject::Reject for Unauthorized {}
+
+// Serves until shutdown resolves, then finishes the requests already in progress
+pub async fn start_api_server(
+    database: Arc<Database>,
+    registry: Arc<SymbolRegistry>,
+    metrics: Arc<Metrics>,
+    rpc_pool: Arc<RpcPool>,
+    health: Arc<HealthChecker>,
+    admin_token: Option<String>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) {
     let db_filter = warp::any().map(move || database.clone());
+    let registry_filter = warp::any().map(move || registry.clone());
 
     let price_route = warp::path!("oracle" / "price" / String)
         .and(db_filter.clone())
         .and_then(handle_get_price);
 
-    let health_route = warp::path!("oracle" / "health")
-        .map(|| warp::reply::json(&serde_json::json!({"status": "ok"})));
+    let reconciliation_route = warp::path!("oracle" / "reconciliation" / String)
+        .and(db_filter.clone())
+        .and_then(handle_get_reconciliation);
+
+    // Liveness: 503 only when this process' update loop has stopped making progress
+    let liveness_health = health.clone();
+    let health_route = warp::path!("oracle" / "health").map(move || {
+        let report = liveness_health.liveness();
+        warp::reply::with_status(warp::reply::json(&report), status_code(report.status))
+    });
+
+    // Readiness: 503 when Redis, every RPC endpoint or every symbol's price is unavailable; degraded is still 200
+    let ready_route = warp::path!("oracle" / "ready").then(move || {
+        let health = health.clone();
+        async move {
+            let report = health.readiness().await;
+            warp::reply::with_status(warp::reply::json(&report), status_code(report.status))
+        }
+    });
+
+    // Prometheus text exposition format
+    let metrics_route = warp::path!("metrics").map(move || {
+        warp::reply::with_header(metrics.render(&rpc_pool.status()), "content-type", "text/plain; version=0.0.4")
+    });
+
+    let admin_auth = admin_auth(admin_token);
+
+    let list_route = warp::get()
+        .and(warp::path!("oracle" / "admin" / "symbols"))
+        .and(admin_auth.clone())
+        .and(registry_filter.clone())
+        .then(|registry: Arc<SymbolRegistry>| async move { warp::reply::json(&registry.list().await) });
+
+    let upsert_route = warp::put()
+        .and(warp::path!("oracle" / "admin" / "symbols" / String))
+        .and(admin_auth.clone())
+        .and(warp::body::json())
+        .and(registry_filter.clone())
+        .then(handle_upsert_symbol);
+
+    let remove_route = warp::delete()
+        .and(warp::path!("oracle" / "admin" / "symbols" / String))
+        .and(admin_auth.clone())
+        .and(registry_filter.clone())
+        .then(|symbol: String, registry: Arc<SymbolRegistry>| async move {
+            admin_reply(registry.remove(&symbol).await)
+        });
+
+    let enable_route = warp::post()
+        .and(warp::path!("oracle" / "admin" / "symbols" / String / "enable"))
+        .and(admin_auth.clone())
+        .and(registry_filter.clone())
+        .then(|symbol: String, registry: Arc<SymbolRegistry>| async move {
+            admin_reply(registry.set_enabled(&symbol, true).await)
+        });
+
+    let disable_route = warp::post()
+        .and(warp::path!("oracle" / "admin" / "symbols" / String / "disable"))
+        .and(admin_auth)
+        .and(registry_filter)
+        .then(|symbol: String, registry: Arc<SymbolRegistry>| async move {
+            admin_reply(registry.set_enabled(&symbol, false).await)
+        });
 
-    let routes = price_route.or(health_route);
+    let admin_routes = list_route
+        .or(upsert_route)
+        .or(remove_route)
+        .or(enable_route)
+        .or(disable_route);
+
+    let routes = price_route
+        .or(reconciliation_route)
+        .or(health_route)
+        .or(ready_route)
+        .or(metrics_route)
+        .or(admin_routes)
+        .recover(handle_rejection);
 
     println!("API Server starting on port 3030...");
-    warp::serve(routes).run(([0, 0, 0, 0], 3030)).await;
+    let (_, server) = warp::serve(routes).bind_with_graceful_shutdown(([0, 0, 0, 0], 3030), shutdown);
+    server.await;
 }
 
-async fn handle_get_price(symbol: String, db: Arc<Database>) -> Result<impl warp::Reply, warp::Rejection> {
+async fn handle_get_price(symbol: String, db: Arc<Database>) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
     match db.get_cached_price(&symbol).await {
-        Ok(Some(price)) => Ok(warp::reply::json(&price)),
-        Ok(None) => Err(warp::reject::not_found()),
+        Ok(Some(price)) => Ok(Box::new(warp::reply::json(&price))),
+        Ok(None) => match db.get_symbol_status(&symbol).await {
+            // Price withheld (quorum lost, sources disagree): report why rather than a plain 404
+            Ok(Some(status)) => Ok(Box::new(warp::reply::with_status(
+                warp::reply::json(&status),
+                StatusCode::SERVICE_UNAVAILABLE,
+            ))),
+            _ => Err(warp::reject::not_found()),
+        },
         Err(_) => Err(warp::reject::not_found()), // Simplify error handling for demo
     }
 }
+
+async fn handle_get_reconciliation(symbol: String, db: Arc<Database>) -> Result<impl warp::Reply, warp::Rejection> {
+    match db.latest_reconciliation(&symbol).await {
+        Ok(Some(reconciliation)) => Ok(warp::reply::json(&reconciliation)),
+        _ => Err(warp::reject::not_found()),
+    }
+}
+
+async fn handle_upsert_symbol(symbol: String, spec: SymbolSpec, registry: Arc<SymbolRegistry>) -> warp::reply::WithStatus<warp::reply::Json> {
+    if spec.symbol != symbol {
+        return error_reply(StatusCode::BAD_REQUEST, format!("Body is for {}, not {}", spec.symbol, symbol));
+    }
+    admin_reply(registry.upsert(spec).await)
+}
+
+fn status_code(status: HealthStatus) -> StatusCode {
+    match status {
+        HealthStatus::Healthy | HealthStatus::Degraded => StatusCode::OK,
+        HealthStatus::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+fn admin_reply(result: Result<(), RegistryError>) -> warp::reply::WithStatus<warp::reply::Json> {
+    match result {
+        Ok(()) => warp::reply::with_status(warp::reply::json(&serde_json::json!({"status": "ok"})), StatusCode::OK),
+        Err(e @ RegistryError::Invalid(_)) => error_reply(StatusCode::BAD_REQUEST, e.to_string()),
+        Err(e @ RegistryError::UnknownSymbol(_)) => error_reply(StatusCode::NOT_FOUND, e.to_string()),
+        Err(e @ RegistryError::Storage(_)) => error_reply(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+fn error_reply(status: StatusCode, error: String) -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(warp::reply::json(&serde_json::json!({"error": error})), status)
+}
+
+async fn handle_rejection(rejection: warp::Rejection) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
+    if rejection.find::<Unauthorized>().is_some() {
+        return Ok(Box::new(error_reply(StatusCode::UNAUTHORIZED, "Missing or invalid admin token".to_string())));
+    }
+    Err(rejection)
+}
+
+// Every admin route requires "Authorization: Bearer <ADMIN_API_TOKEN>"; without a token they are disabled
+fn admin_auth(admin_token: Option<String>) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let authorized = match (&admin_token, header) {
+                (Some(token), Some(header)) => header
+                    .strip_prefix("Bearer ")
+                    .is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes())),
+                _ => false,
+            };
+            async move {
+                if authorized {
+                    Ok(())
+                } else {
+                    Err(warp::reject::custom(Unauthorized))
+                }
+            }
+        })
+        .untuple_one()
+}
+
+// Compares every byte so response timing doesn't reveal how much of the token matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An admin route with the real auth filter and rejection handling, minus the registry
+    fn admin_route(token: Option<&str>) -> impl Filter<Extract = (Box<dyn warp::Reply>,), Error = warp::Rejection> + Clone {
+        warp::path!("oracle" / "admin" / "symbols")
+            .and(admin_auth(token.map(str::to_string)))
+            .map(|| Box::new(warp::reply::json(&serde_json::json!({"status": "ok"}))) as Box<dyn warp::Reply>)
+            .recover(handle_rejection)
+            .unify()
+    }
+
+    async fn status(token: Option<&str>, authorization: Option<&str>) -> StatusCode {
+        let mut request = warp::test::request().path("/oracle/admin/symbols");
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        request.reply(&admin_route(token)).await.status()
+    }
+
+    #[tokio::test]
+    async fn admin_routes_require_the_bearer_token() {
+        assert_eq!(status(Some("secret"), None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("secret"), Some("Bearer wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("secret"), Some("Bearer secre")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("secret"), Some("secret")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("secret"), Some("Bearer secret")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn admin_routes_are_disabled_without_a_configured_token() {
+        assert_eq!(status(None, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(None, Some("Bearer ")).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn unauthorized_replies_explain_why() {
+        let response = warp::test::request().path("/oracle/admin/symbols").reply(&admin_route(Some("secret"))).await;
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(body["error"], "Missing or invalid admin token");
+    }
+}
@@ -3,16 +3,37 @@ use sqlx::postgres::PgPoolOptions;
 use sqlx::Pool;
 use sqlx::Postgres;
 use anyhow::Result;
-use crate::price_aggregator::ConsensusPrice;
+use crate::price_aggregator::{ConsensusPrice, SymbolStatus};
+use crate::metrics::Metrics;
+use crate::reconciliation::Reconciliation;
+use crate::symbol_config::SymbolOverride;
+use oracle_core::FixedPrice;
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Prices come back as NUMERIC::TEXT and are parsed into FixedPrice
+#[derive(sqlx::FromRow)]
+struct ReconciliationRow {
+    backend_price: String,
+    onchain_pyth_price: Option<String>,
+    pyth_divergence_bps: Option<i64>,
+    pyth_error: Option<String>,
+    onchain_consensus_price: Option<String>,
+    consensus_divergence_bps: Option<i64>,
+    consensus_error: Option<String>,
+    timestamp: chrono::NaiveDateTime,
+}
 
 pub struct Database {
     pg_pool: Pool<Postgres>,
     redis_client: redis::Client,
+    metrics: Arc<Metrics>,
 }
 
 impl Database {
-    pub async fn new(pg_url: &str, redis_url: &str) -> Result<Self> {
+    pub async fn new(pg_url: &str, redis_url: &str, metrics: Arc<Metrics>) -> Result<Self> {
         let pg_pool = PgPoolOptions::new()
             .max_connections(5)
             .connect(pg_url)
@@ -23,9 +44,34 @@ impl Database {
         Ok(Self {
             pg_pool,
             redis_client,
+            metrics,
         })
     }
 
+    // Records the write's latency and outcome under the given store and operation
+    async fn timed<T>(&self, store: &str, operation: &str, write: impl Future<Output = Result<T>>) -> Result<T> {
+        let started = Instant::now();
+        let result = write.await;
+        self.metrics.record_write(store, operation, started.elapsed(), &result);
+        result
+    }
+
+    // Waits for checked-out connections to be returned, then closes the pool
+    pub async fn close(&self) {
+        self.pg_pool.close().await;
+    }
+
+    pub async fn ping_redis(&self) -> Result<()> {
+        let mut con = self.redis_client.get_async_connection().await?;
+        redis::cmd("PING").query_async::<_, String>(&mut con).await?;
+        Ok(())
+    }
+
+    pub async fn ping_postgres(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pg_pool).await?;
+        Ok(())
+    }
+
     pub async fn migrate(&self) -> Result<()> {
         sqlx::migrate!("./migrations")
             .run(&self.pg_pool)
@@ -34,15 +80,87 @@ impl Database {
     }
 
     pub async fn cache_price(&self, symbol: &str, price: &ConsensusPrice) -> Result<()> {
-        let mut con = self.redis_client.get_async_connection().await?;
-        let key = format!("price:{}", symbol);
-        let json = serde_json::to_string(price)?;
+        self.timed("redis", "cache_price", async {
+            let mut con = self.redis_client.get_async_connection().await?;
+            let key = format!("price:{}", symbol);
+            let json = serde_json::to_string(price)?;
         
-        // Set with expiry (e.g., 5 seconds) to ensure we don't serve very old data if system crashes
-        con.set_ex::<_, _, ()>(key, json, 5).await?;
+            // Set with expiry (e.g., 5 seconds) to ensure we don't serve very old data if system crashes
+            redis::pipe()
+                .set_ex(key, json, 5)
+                .del(format!("status:{}", symbol))
+                .query_async::<_, ()>(&mut con)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Replaces the cached price with the reason it is withheld so the API stops serving a degraded price
+    pub async fn cache_symbol_status(&self, symbol: &str, status: &SymbolStatus) -> Result<()> {
+        self.timed("redis", "cache_symbol_status", async {
+            let mut con = self.redis_client.get_async_connection().await?;
+            let json = serde_json::to_string(status)?;
+
+            redis::pipe()
+                .del(format!("price:{}", symbol))
+                .set_ex(format!("status:{}", symbol), json, 5)
+                .query_async::<_, ()>(&mut con)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    // SET NX with a lease: true if this instance now holds the key
+    pub async fn acquire_lease(&self, key: &str, holder: &str, lease: Duration) -> Result<bool> {
+        let mut con = self.redis_client.get_async_connection().await?;
+        let acquired: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(holder)
+            .arg("NX")
+            .arg("PX")
+            .arg(lease.as_millis() as u64)
+            .query_async(&mut con)
+            .await?;
+        Ok(acquired.is_some())
+    }
+
+    // Extends the lease only if this instance still holds it
+    pub async fn renew_lease(&self, key: &str, holder: &str, lease: Duration) -> Result<bool> {
+        let mut con = self.redis_client.get_async_connection().await?;
+        let renewed: i64 = redis::Script::new(
+            "if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('PEXPIRE', KEYS[1], ARGV[2]) else return 0 end",
+        )
+        .key(key)
+        .arg(holder)
+        .arg(lease.as_millis() as u64)
+        .invoke_async(&mut con)
+        .await?;
+        Ok(renewed == 1)
+    }
+
+    // Deletes the lease only if this instance still holds it
+    pub async fn release_lease(&self, key: &str, holder: &str) -> Result<()> {
+        let mut con = self.redis_client.get_async_connection().await?;
+        redis::Script::new("if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('DEL', KEYS[1]) else return 0 end")
+            .key(key)
+            .arg(holder)
+            .invoke_async::<_, i64>(&mut con)
+            .await?;
         Ok(())
     }
 
+    pub async fn get_symbol_status(&self, symbol: &str) -> Result<Option<SymbolStatus>> {
+        let mut con = self.redis_client.get_async_connection().await?;
+        let json: Option<String> = con.get(format!("status:{}", symbol)).await?;
+
+        match json {
+            Some(j) => Ok(Some(serde_json::from_str(&j)?)),
+            None => Ok(None),
+        }
+    }
+
     pub async fn get_cached_price(&self, symbol: &str) -> Result<Option<ConsensusPrice>> {
         let mut con = self.redis_client.get_async_connection().await?;
         let key = format!("price:{}", symbol);
@@ -54,20 +172,188 @@ impl Database {
         }
     }
 
+    // Fraction of recent consensus rounds each source contributed to
+    pub async fn source_agreement_rates(&self, symbol: &str, window_secs: i64) -> Result<HashMap<String, f64>> {
+        let rows: Vec<(String, f64)> = sqlx::query_as(
+            r#"
+            SELECT s.source, AVG(CASE WHEN s.used THEN 1.0 ELSE 0.0 END)::DOUBLE PRECISION
+            FROM price_history_sources s
+            JOIN price_history h ON h.id = s.price_history_id
+            WHERE h.symbol = $1 AND h.timestamp > (NOW() AT TIME ZONE 'UTC') - make_interval(secs => $2)
+            GROUP BY s.source
+            "#
+        )
+        .bind(symbol)
+        .bind(window_secs as f64)
+        .fetch_all(&self.pg_pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    pub async fn last_consensus_price(&self, symbol: &str) -> Result<Option<FixedPrice>> {
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT price::TEXT FROM price_history WHERE symbol = $1 ORDER BY timestamp DESC LIMIT 1"
+        )
+        .bind(symbol)
+        .fetch_optional(&self.pg_pool)
+        .await?;
+
+        row.map(|(price,)| price.parse().map_err(anyhow::Error::from)).transpose()
+    }
+
     pub async fn save_price_history(&self, symbol: &str, price: &ConsensusPrice) -> Result<()> {
+        self.timed("postgres", "save_price_history", async {
+            let mut tx = self.pg_pool.begin().await?;
+
+            let (history_id,): (i32,) = sqlx::query_as(
+                r#"
+                INSERT INTO price_history (symbol, price, confidence, timestamp, sources_used)
+                VALUES ($1, $2::NUMERIC, $3::NUMERIC, $4, $5)
+                RETURNING id
+                "#
+            )
+            .bind(symbol)
+            // Bound as decimal strings so Postgres stores the exact mantissa/exponent value
+            .bind(price.price.to_string())
+            .bind(price.confidence.to_string())
+            .bind(chrono::DateTime::from_timestamp(price.timestamp, 0).map(|dt| dt.naive_utc()))
+            .bind(price.sources_used as i32)
+            .fetch_one(&mut tx)
+            .await?;
+
+            for source in &price.sources {
+                sqlx::query(
+                    r#"
+                    INSERT INTO price_history_sources
+                        (price_history_id, source, used, price, confidence, age_secs, deviation_bps)
+                    VALUES ($1, $2, TRUE, $3::NUMERIC, $4::NUMERIC, $5, $6)
+                    "#
+                )
+                .bind(history_id)
+                .bind(&source.source)
+                .bind(source.price.to_string())
+                .bind(source.confidence.to_string())
+                .bind(source.age_secs)
+                .bind(source.deviation_bps as i64)
+                .execute(&mut tx)
+                .await?;
+            }
+
+            for rejected in &price.rejected {
+                sqlx::query(
+                    r#"
+                    INSERT INTO price_history_sources
+                        (price_history_id, source, used, price, rejection_reason, rejection_detail)
+                    VALUES ($1, $2, FALSE, $3::NUMERIC, $4, $5)
+                    "#
+                )
+                .bind(history_id)
+                .bind(&rejected.source)
+                .bind(rejected.price.map(|p| p.to_string()))
+                .bind(rejected.reason.kind())
+                .bind(serde_json::to_string(&rejected.reason)?)
+                .execute(&mut tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn load_symbol_overrides(&self) -> Result<Vec<SymbolOverride>> {
+        let rows: Vec<(String, Option<String>, bool, bool)> = sqlx::query_as(
+            "SELECT symbol, config::TEXT, enabled, removed FROM symbol_overrides"
+        )
+        .fetch_all(&self.pg_pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|(symbol, config, enabled, removed)| {
+                Ok(SymbolOverride {
+                    symbol,
+                    config: config.map(|json| serde_json::from_str(&json)).transpose()?,
+                    enabled,
+                    removed,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn save_symbol_override(&self, overrides: &SymbolOverride) -> Result<()> {
+        let config = overrides.config.as_ref().map(serde_json::to_string).transpose()?;
         sqlx::query(
             r#"
-            INSERT INTO price_history (symbol, price, confidence, timestamp, sources_used)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO symbol_overrides (symbol, config, enabled, removed, updated_at)
+            VALUES ($1, $2::JSONB, $3, $4, NOW() AT TIME ZONE 'UTC')
+            ON CONFLICT (symbol) DO UPDATE
+            SET config = EXCLUDED.config, enabled = EXCLUDED.enabled, removed = EXCLUDED.removed, updated_at = EXCLUDED.updated_at
             "#
         )
-        .bind(symbol)
-        .bind(price.price)
-        .bind(price.confidence)
-        .bind(chrono::NaiveDateTime::from_timestamp_opt(price.timestamp, 0))
-        .bind(price.sources_used as i32)
+        .bind(&overrides.symbol)
+        .bind(config)
+        .bind(overrides.enabled)
+        .bind(overrides.removed)
         .execute(&self.pg_pool)
         .await?;
         Ok(())
     }
+
+    pub async fn save_reconciliation(&self, reconciliation: &Reconciliation) -> Result<()> {
+        self.timed("postgres", "save_reconciliation", async {
+            sqlx::query(
+                r#"
+                INSERT INTO reconciliation_history
+                    (symbol, backend_price, onchain_pyth_price, pyth_divergence_bps, pyth_error,
+                     onchain_consensus_price, consensus_divergence_bps, consensus_error, timestamp)
+                VALUES ($1, $2::NUMERIC, $3::NUMERIC, $4, $5, $6::NUMERIC, $7, $8, $9)
+                "#
+            )
+            .bind(&reconciliation.symbol)
+            .bind(reconciliation.backend_price.to_string())
+            .bind(reconciliation.onchain_pyth_price.map(|p| p.to_string()))
+            .bind(reconciliation.pyth_divergence_bps.map(|bps| bps as i64))
+            .bind(&reconciliation.pyth_error)
+            .bind(reconciliation.onchain_consensus_price.map(|p| p.to_string()))
+            .bind(reconciliation.consensus_divergence_bps.map(|bps| bps as i64))
+            .bind(&reconciliation.consensus_error)
+            .bind(chrono::DateTime::from_timestamp(reconciliation.timestamp, 0).map(|dt| dt.naive_utc()))
+            .execute(&self.pg_pool)
+            .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn latest_reconciliation(&self, symbol: &str) -> Result<Option<Reconciliation>> {
+        let row: Option<ReconciliationRow> = sqlx::query_as(
+            r#"
+            SELECT backend_price::TEXT AS backend_price, onchain_pyth_price::TEXT AS onchain_pyth_price,
+                   pyth_divergence_bps, pyth_error, onchain_consensus_price::TEXT AS onchain_consensus_price,
+                   consensus_divergence_bps, consensus_error, timestamp
+            FROM reconciliation_history WHERE symbol = $1 ORDER BY timestamp DESC LIMIT 1
+            "#
+        )
+        .bind(symbol)
+        .fetch_optional(&self.pg_pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let parse = |price: Option<String>| price.map(|p| p.parse::<FixedPrice>()).transpose();
+        Ok(Some(Reconciliation {
+            symbol: symbol.to_string(),
+            backend_price: row.backend_price.parse()?,
+            onchain_pyth_price: parse(row.onchain_pyth_price)?,
+            pyth_divergence_bps: row.pyth_divergence_bps.map(|bps| bps as u64),
+            pyth_error: row.pyth_error,
+            onchain_consensus_price: parse(row.onchain_consensus_price)?,
+            consensus_divergence_bps: row.consensus_divergence_bps.map(|bps| bps as u64),
+            consensus_error: row.consensus_error,
+            timestamp: row.timestamp.and_utc().timestamp(),
+        }))
+    }
 }
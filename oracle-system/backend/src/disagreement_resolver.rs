@@ -0,0 +1,344 @@
+use crate::database::Database;
+use crate::price_aggregator::SourcePrice;
+use oracle_core::consensus;
+use oracle_core::FixedPrice;
+use serde::{Serialize, Deserialize};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use log::error;
+
+// Weight of each signal in a source's trust score
+const CONFIDENCE_WEIGHT: f64 = 0.25;
+const HISTORY_WEIGHT: f64 = 0.25;
+const CONTINUITY_WEIGHT: f64 = 0.25;
+const RELIABILITY_WEIGHT: f64 = 0.25;
+// Score used for a signal that has no data yet (no history, unknown source)
+const NEUTRAL_SCORE: f64 = 0.5;
+// Agreement history is refreshed in the background on this interval; after failed refreshes the last
+// loaded rates are used until they are this old, then disagreements are withheld instead
+pub const HISTORY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_HISTORY_AGE: Duration = Duration::from_secs(120);
+
+// Where each source's share of recent consensus rounds, and the last published price, come from;
+// Postgres in production
+#[async_trait]
+pub trait AgreementHistory: Send + Sync {
+    async fn source_agreement_rates(&self, symbol: &str, window_secs: i64) -> Result<HashMap<String, f64>>;
+    async fn last_consensus_price(&self, symbol: &str) -> Result<Option<FixedPrice>>;
+}
+
+#[async_trait]
+impl AgreementHistory for Database {
+    async fn source_agreement_rates(&self, symbol: &str, window_secs: i64) -> Result<HashMap<String, f64>> {
+        Database::source_agreement_rates(self, symbol, window_secs).await
+    }
+
+    async fn last_consensus_price(&self, symbol: &str) -> Result<Option<FixedPrice>> {
+        Database::last_consensus_price(self, symbol).await
+    }
+}
+
+enum History {
+    Loaded { agreement: HashMap<String, f64>, at: Instant },
+    // The last refresh failed and no recent rates are left to fall back on
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceScore {
+    pub source: String,
+    pub price: FixedPrice,
+    // Narrower confidence interval relative to price scores higher
+    pub confidence: f64,
+    // Share of recent consensus rounds this source contributed to
+    pub history_agreement: f64,
+    // Closeness to the last published consensus price
+    pub continuity: f64,
+    // Configured prior for the source
+    pub reliability: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnhealthyReport {
+    pub reason: String,
+    pub scores: Vec<SourceScore>,
+    pub timestamp: i64,
+}
+
+pub enum Resolution {
+    Trust { source: String, scores: Vec<SourceScore> },
+    Unhealthy(UnhealthyReport),
+}
+
+// Decides which of two disagreeing sources to believe, since their median cannot tell them apart.
+// Resolving reads only in-memory state: agreement rates are cached per symbol by refresh, and the
+// last consensus price is recorded by the update loop.
+pub struct DisagreementResolver {
+    store: Arc<dyn AgreementHistory>,
+    reliability: HashMap<String, f64>,
+    history_window_secs: i64,
+    // Minimum score lead the winner needs before it is trusted on its own
+    min_margin: f64,
+    history: RwLock<HashMap<String, History>>,
+    last_prices: RwLock<HashMap<String, FixedPrice>>,
+}
+
+impl DisagreementResolver {
+    pub fn new(store: Arc<dyn AgreementHistory>, reliability: HashMap<String, f64>, history_window_secs: i64, min_margin: f64) -> Self {
+        Self {
+            store,
+            reliability,
+            history_window_secs,
+            min_margin,
+            history: RwLock::new(HashMap::new()),
+            last_prices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Reloads the agreement rates of the given symbols and forgets any others. The last published
+    // price is only loaded until the update loop has observed one, e.g. after a restart.
+    pub async fn refresh(&self, symbols: &[String]) {
+        for symbol in symbols {
+            if !self.last_prices.read().unwrap().contains_key(symbol) {
+                match self.store.last_consensus_price(symbol).await {
+                    Ok(Some(price)) => {
+                        self.last_prices.write().unwrap().entry(symbol.clone()).or_insert(price);
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!("Failed to load the last consensus price for {}: {}", symbol, e),
+                }
+            }
+
+            let loaded = self.store.source_agreement_rates(symbol, self.history_window_secs).await;
+            let mut history = self.history.write().unwrap();
+            match loaded {
+                Ok(agreement) => {
+                    history.insert(symbol.clone(), History::Loaded { agreement, at: Instant::now() });
+                }
+                Err(e) => {
+                    error!("Failed to load source agreement history for {}: {}", symbol, e);
+                    let recent = matches!(history.get(symbol), Some(History::Loaded { at, .. }) if at.elapsed() < MAX_HISTORY_AGE);
+                    if !recent {
+                        history.insert(symbol.clone(), History::Unavailable(e.to_string()));
+                    }
+                }
+            }
+        }
+
+        self.history.write().unwrap().retain(|symbol, _| symbols.contains(symbol));
+        self.last_prices.write().unwrap().retain(|symbol, _| symbols.contains(symbol));
+    }
+
+    pub fn observe_consensus(&self, symbol: &str, price: FixedPrice) {
+        self.last_prices.write().unwrap().insert(symbol.to_string(), price);
+    }
+
+    pub fn resolve(&self, symbol: &str, candidates: &[SourcePrice]) -> Resolution {
+        let now = chrono::Utc::now().timestamp();
+        // Not loaded yet scores as no history; a failed load withholds rather than guess
+        let agreement = match self.history.read().unwrap().get(symbol) {
+            Some(History::Loaded { agreement, .. }) => agreement.clone(),
+            Some(History::Unavailable(e)) => {
+                return Resolution::Unhealthy(UnhealthyReport {
+                    reason: format!("Sources disagree and their agreement history is unavailable: {}", e),
+                    scores: Vec::new(),
+                    timestamp: now,
+                })
+            }
+            None => HashMap::new(),
+        };
+        let last_price = self.last_prices.read().unwrap().get(symbol).copied();
+
+        let mut scores: Vec<SourceScore> = candidates
+            .iter()
+            .map(|candidate| self.score(candidate, agreement.get(&candidate.source).copied(), last_price))
+            .collect();
+        scores.sort_by(|a, b| b.total.total_cmp(&a.total));
+
+        match scores.as_slice() {
+            [best, runner_up, ..] if best.total - runner_up.total >= self.min_margin => Resolution::Trust {
+                source: best.source.clone(),
+                scores,
+            },
+            [_, _, ..] => Resolution::Unhealthy(UnhealthyReport {
+                reason: format!("Sources disagree and no source leads by the required {:.2} margin", self.min_margin),
+                scores,
+                timestamp: now,
+            }),
+            _ => Resolution::Unhealthy(UnhealthyReport {
+                reason: "Not enough sources to resolve a disagreement".to_string(),
+                scores,
+                timestamp: now,
+            }),
+        }
+    }
+
+    fn score(&self, candidate: &SourcePrice, history_agreement: Option<f64>, last_price: Option<FixedPrice>) -> SourceScore {
+        let confidence = ratio_score(candidate.confidence, candidate.price).unwrap_or(NEUTRAL_SCORE);
+        let continuity = last_price
+            .and_then(|last| deviation_score(candidate.price, last))
+            .unwrap_or(NEUTRAL_SCORE);
+        let history_agreement = history_agreement.unwrap_or(NEUTRAL_SCORE);
+        let reliability = self.reliability.get(&candidate.source).copied().unwrap_or(NEUTRAL_SCORE);
+
+        SourceScore {
+            source: candidate.source.clone(),
+            price: candidate.price,
+            confidence,
+            history_agreement,
+            continuity,
+            reliability,
+            total: CONFIDENCE_WEIGHT * confidence
+                + HISTORY_WEIGHT * history_agreement
+                + CONTINUITY_WEIGHT * continuity
+                + RELIABILITY_WEIGHT * reliability,
+        }
+    }
+}
+
+// 1.0 for a zero-width interval, 0.5 at 1% of price, approaching 0 as it widens
+fn ratio_score(confidence: FixedPrice, price: FixedPrice) -> Option<f64> {
+    let conf_bps = consensus::confidence_bps(&price, &confidence).ok()?;
+    Some(1.0 / (1.0 + conf_bps as f64 / 100.0))
+}
+
+// 1.0 when equal to the reference, 0.5 at 1% away, approaching 0 further out
+fn deviation_score(price: FixedPrice, reference: FixedPrice) -> Option<f64> {
+    let expo = consensus::common_expo(&[price, reference]);
+    let price = consensus::mantissa_at(&price, expo).ok()?;
+    let reference = consensus::mantissa_at(&reference, expo).ok()?;
+    let bps = consensus::deviation_bps(price, reference);
+    Some(1.0 / (1.0 + bps as f64 / 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+    use std::sync::Mutex;
+
+    // Serves fixed agreement rates and no last price, or fails while `failing` is set
+    struct FakeHistory {
+        agreement: HashMap<String, f64>,
+        failing: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl AgreementHistory for FakeHistory {
+        async fn source_agreement_rates(&self, _symbol: &str, _window_secs: i64) -> Result<HashMap<String, f64>> {
+            if *self.failing.lock().unwrap() {
+                bail!("connection refused");
+            }
+            Ok(self.agreement.clone())
+        }
+
+        async fn last_consensus_price(&self, _symbol: &str) -> Result<Option<FixedPrice>> {
+            if *self.failing.lock().unwrap() {
+                bail!("connection refused");
+            }
+            Ok(None)
+        }
+    }
+
+    fn resolver(agreement: &[(&str, f64)], reliability: &[(&str, f64)]) -> (DisagreementResolver, Arc<FakeHistory>) {
+        let store = Arc::new(FakeHistory {
+            agreement: agreement.iter().map(|(source, rate)| (source.to_string(), *rate)).collect(),
+            failing: Mutex::new(false),
+        });
+        let reliability = reliability.iter().map(|(source, prior)| (source.to_string(), *prior)).collect();
+        (DisagreementResolver::new(store.clone(), reliability, 300, 0.15), store)
+    }
+
+    fn candidate(source: &str, price: i64, confidence: i64) -> SourcePrice {
+        SourcePrice {
+            price: FixedPrice::new(price, -2),
+            confidence: FixedPrice::new(confidence, -2),
+            timestamp: 0,
+            slot: 0,
+            source: source.to_string(),
+        }
+    }
+
+    fn symbols() -> Vec<String> {
+        vec!["SOL".to_string()]
+    }
+
+    #[test]
+    fn score_weighs_each_signal_equally() {
+        let (resolver, _) = resolver(&[], &[("Pyth", 0.9)]);
+
+        // 100 bps confidence and 100 bps from the last price both score 0.5
+        let score = resolver.score(&candidate("Pyth", 10_000, 100), Some(0.8), Some(FixedPrice::new(10_100, -2)));
+        assert!((score.confidence - 0.5).abs() < 0.01);
+        assert!((score.continuity - 0.5).abs() < 0.01);
+        assert_eq!(score.history_agreement, 0.8);
+        assert_eq!(score.reliability, 0.9);
+        assert!((score.total - (0.5 + 0.8 + 0.5 + 0.9) / 4.0).abs() < 0.01);
+
+        // Missing signals fall back to neutral
+        let score = resolver.score(&candidate("Unknown", 10_000, 0), None, None);
+        assert_eq!(score.confidence, 1.0);
+        assert_eq!((score.history_agreement, score.continuity, score.reliability), (NEUTRAL_SCORE, NEUTRAL_SCORE, NEUTRAL_SCORE));
+    }
+
+    #[tokio::test]
+    async fn trusts_the_source_that_leads_by_the_margin() {
+        let (resolver, _) = resolver(&[("Pyth", 1.0), ("Switchboard", 0.2)], &[("Pyth", 0.9), ("Switchboard", 0.6)]);
+        resolver.refresh(&symbols()).await;
+        resolver.observe_consensus("SOL", FixedPrice::new(10_000, -2));
+
+        let resolution = resolver.resolve("SOL", &[candidate("Pyth", 10_000, 5), candidate("Switchboard", 10_300, 5)]);
+
+        let Resolution::Trust { source, scores } = resolution else { panic!("expected a trusted source") };
+        assert_eq!(source, "Pyth");
+        assert!(scores[0].total - scores[1].total >= 0.15);
+    }
+
+    #[tokio::test]
+    async fn withholds_when_the_lead_is_inside_the_margin() {
+        // Only a small history lead separates otherwise identical sources
+        let (resolver, _) = resolver(&[("Pyth", 0.7), ("Switchboard", 0.5)], &[]);
+        resolver.refresh(&symbols()).await;
+
+        let resolution = resolver.resolve("SOL", &[candidate("Pyth", 10_000, 5), candidate("Switchboard", 10_300, 5)]);
+
+        let Resolution::Unhealthy(report) = resolution else { panic!("expected unhealthy") };
+        assert!(report.reason.contains("no source leads by the required 0.15 margin"), "{}", report.reason);
+        assert_eq!(report.scores.len(), 2);
+        assert!(report.scores[0].total - report.scores[1].total < 0.15);
+    }
+
+    #[tokio::test]
+    async fn keeps_recent_history_through_a_failed_refresh() {
+        let (resolver, store) = resolver(&[("Pyth", 1.0), ("Switchboard", 0.0)], &[]);
+        resolver.refresh(&symbols()).await;
+
+        *store.failing.lock().unwrap() = true;
+        resolver.refresh(&symbols()).await;
+
+        let resolution = resolver.resolve("SOL", &[candidate("Pyth", 10_000, 5), candidate("Switchboard", 10_300, 5)]);
+        assert!(matches!(resolution, Resolution::Trust { source, .. } if source == "Pyth"));
+    }
+
+    #[tokio::test]
+    async fn withholds_when_history_cannot_be_loaded() {
+        let (resolver, store) = resolver(&[("Pyth", 1.0), ("Switchboard", 0.0)], &[]);
+        *store.failing.lock().unwrap() = true;
+        resolver.refresh(&symbols()).await;
+
+        let resolution = resolver.resolve("SOL", &[candidate("Pyth", 10_000, 5), candidate("Switchboard", 10_300, 5)]);
+
+        let Resolution::Unhealthy(report) = resolution else { panic!("expected unhealthy") };
+        assert_eq!(report.reason, "Sources disagree and their agreement history is unavailable: connection refused");
+
+        // A later successful refresh restores resolution
+        *store.failing.lock().unwrap() = false;
+        resolver.refresh(&symbols()).await;
+        let resolution = resolver.resolve("SOL", &[candidate("Pyth", 10_000, 5), candidate("Switchboard", 10_300, 5)]);
+        assert!(matches!(resolution, Resolution::Trust { .. }));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::database::Database;
+use crate::oracle_manager::OracleManager;
+use crate::rpc_pool::{EndpointStatus, RpcPool};
+use serde::Serialize;
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+// Dependency checks that take longer than this count as failed
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+// An endpoint erroring on more than this share of recent calls is not counted as usable
+const MAX_ENDPOINT_ERROR_RATE: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcHealth {
+    pub status: HealthStatus,
+    pub usable_endpoints: usize,
+    pub endpoints: Vec<EndpointStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Freshness {
+    Fresh,
+    // Cached, but older than the symbol's max_staleness
+    Stale,
+    // Withheld by the backend (quorum lost, sources disagree)
+    Withheld,
+    // Neither a price nor a status is cached
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolHealth {
+    pub symbol: String,
+    pub freshness: Freshness,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_secs: Option<i64>,
+}
+
+// Everything the API depends on to serve fresh prices
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub status: HealthStatus,
+    pub leader: bool,
+    pub redis: Check,
+    pub postgres: Check,
+    pub rpc: RpcHealth,
+    pub symbols: Vec<SymbolHealth>,
+    pub timestamp: i64,
+}
+
+// Only this process' own update loop, so a dependency outage doesn't get every replica restarted
+#[derive(Debug, Clone, Serialize)]
+pub struct LivenessReport {
+    pub status: HealthStatus,
+    pub leader: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_round_secs_ago: Option<i64>,
+}
+
+pub struct HealthChecker {
+    database: Arc<Database>,
+    rpc_pool: Arc<RpcPool>,
+    manager: Arc<OracleManager>,
+    max_slot_lag: u64,
+    // Longest the update loop may go without finishing a tick before the process counts as stuck
+    max_idle_secs: i64,
+}
+
+impl HealthChecker {
+    pub fn new(
+        database: Arc<Database>,
+        rpc_pool: Arc<RpcPool>,
+        manager: Arc<OracleManager>,
+        max_slot_lag: u64,
+        max_idle_secs: i64,
+    ) -> Self {
+        Self {
+            database,
+            rpc_pool,
+            manager,
+            max_slot_lag,
+            max_idle_secs,
+        }
+    }
+
+    pub fn liveness(&self) -> LivenessReport {
+        let now = chrono::Utc::now().timestamp();
+        let last_round_secs_ago = self.manager.last_round().map(|at| now - at);
+        // With no symbols configured there are no rounds to wait for
+        let stuck = !self.manager.configs().is_empty()
+            && last_round_secs_ago.is_none_or(|secs| secs > self.max_idle_secs);
+
+        LivenessReport {
+            status: if stuck { HealthStatus::Unhealthy } else { HealthStatus::Healthy },
+            leader: self.manager.is_leader(),
+            last_round_secs_ago,
+        }
+    }
+
+    pub async fn readiness(&self) -> ReadinessReport {
+        let (redis, postgres) = tokio::join!(
+            check(self.database.ping_redis(), HealthStatus::Unhealthy),
+            // Only history writes need Postgres; prices are still served from Redis
+            check(self.database.ping_postgres(), HealthStatus::Degraded),
+        );
+        let rpc = self.rpc_health();
+        let symbols = if redis.status == HealthStatus::Healthy {
+            self.symbol_health().await
+        } else {
+            Vec::new()
+        };
+
+        let fresh = symbols.iter().filter(|symbol| symbol.freshness == Freshness::Fresh).count();
+        let symbols_status = match fresh {
+            _ if fresh == symbols.len() => HealthStatus::Healthy,
+            0 => HealthStatus::Unhealthy,
+            _ => HealthStatus::Degraded,
+        };
+
+        ReadinessReport {
+            status: [redis.status, postgres.status, rpc.status, symbols_status].into_iter().max().unwrap_or(HealthStatus::Healthy),
+            leader: self.manager.is_leader(),
+            redis,
+            postgres,
+            rpc,
+            symbols,
+            timestamp: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    // Unhealthy with no usable endpoint, degraded while any endpoint lags or errors
+    fn rpc_health(&self) -> RpcHealth {
+        let endpoints = self.rpc_pool.status();
+        let usable_endpoints = endpoints
+            .iter()
+            .filter(|e| e.slot > 0 && e.slot_lag <= self.max_slot_lag && e.error_rate <= MAX_ENDPOINT_ERROR_RATE)
+            .count();
+        let status = match usable_endpoints {
+            0 => HealthStatus::Unhealthy,
+            n if n < endpoints.len() => HealthStatus::Degraded,
+            _ => HealthStatus::Healthy,
+        };
+
+        RpcHealth { status, usable_endpoints, endpoints }
+    }
+
+    // Freshness of what the API would serve, read from the shared cache
+    async fn symbol_health(&self) -> Vec<SymbolHealth> {
+        let now = chrono::Utc::now().timestamp();
+        let mut configs = self.manager.configs();
+        configs.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        let mut symbols = Vec::with_capacity(configs.len());
+        for config in configs {
+            let (freshness, age_secs) = match self.database.get_cached_price(&config.symbol).await {
+                Ok(Some(price)) => {
+                    let age = now - price.timestamp;
+                    let freshness = if age > config.thresholds.max_staleness { Freshness::Stale } else { Freshness::Fresh };
+                    (freshness, Some(age))
+                }
+                _ => match self.database.get_symbol_status(&config.symbol).await {
+                    Ok(Some(_)) => (Freshness::Withheld, None),
+                    _ => (Freshness::Missing, None),
+                },
+            };
+            symbols.push(SymbolHealth { symbol: config.symbol, freshness, age_secs });
+        }
+        symbols
+    }
+}
+
+async fn check(probe: impl Future<Output = Result<()>>, failed: HealthStatus) -> Check {
+    match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => Check { status: HealthStatus::Healthy, error: None },
+        Ok(Err(e)) => Check { status: failed, error: Some(e.to_string()) },
+        Err(_) => Check { status: failed, error: Some(format!("No response within {:?}", CHECK_TIMEOUT)) },
+    }
+}
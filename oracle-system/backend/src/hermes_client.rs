@@ -0,0 +1,318 @@
+use crate::pyth_accumulator::{verify_update, FeedId, GuardianSet, PriceFeedMessage};
+use futures::StreamExt;
+use serde::Deserialize;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use log::{info, warn, error};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+struct HermesResponse {
+    binary: HermesBinary,
+}
+
+#[derive(Deserialize)]
+struct HermesBinary {
+    encoding: String,
+    data: Vec<String>,
+}
+
+// Consumes the Hermes price service and keeps the latest verified price per feed ID
+pub struct HermesClient {
+    // Connect timeout only: a total timeout would cut off the long-lived stream
+    http: reqwest::Client,
+    base_url: String,
+    // Bounds one-shot requests, and the wait for each stream chunk before reconnecting
+    timeout: Duration,
+    guardians: GuardianSet,
+    latest: RwLock<HashMap<FeedId, PriceFeedMessage>>,
+}
+
+impl HermesClient {
+    pub fn new(base_url: String, guardians: GuardianSet, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            http: reqwest::Client::builder().connect_timeout(CONNECT_TIMEOUT).build()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            timeout,
+            guardians,
+            latest: RwLock::new(HashMap::new()),
+        })
+    }
+
+    pub fn latest_price(&self, feed_id: &FeedId) -> Option<PriceFeedMessage> {
+        self.latest.read().unwrap().get(feed_id).cloned()
+    }
+
+    // One-shot fetch of the latest update for the given feeds
+    pub async fn fetch_latest(&self, feed_ids: &[FeedId]) -> Result<()> {
+        let url = format!("{}/v2/updates/price/latest", self.base_url);
+        let response: HermesResponse = self
+            .http
+            .get(url)
+            .query(&Self::query(feed_ids))
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        self.apply(response)
+    }
+
+    // Follows the SSE stream, reconnecting after errors, until the process exits
+    pub async fn run_stream(self: Arc<Self>, feed_ids: Vec<FeedId>) {
+        loop {
+            match self.stream_once(&feed_ids).await {
+                Ok(()) => warn!("Hermes stream from {} ended, reconnecting", self.base_url),
+                Err(e) => error!("Hermes stream from {} failed: {}", self.base_url, e),
+            }
+
+            // Catch up on anything missed while disconnected
+            if let Err(e) = self.fetch_latest(&feed_ids).await {
+                error!("Hermes catch-up fetch failed: {}", e);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn stream_once(&self, feed_ids: &[FeedId]) -> Result<()> {
+        let url = format!("{}/v2/updates/price/stream", self.base_url);
+        let request = self.http.get(url).query(&Self::query(feed_ids)).send();
+        let response = tokio::time::timeout(self.timeout, request)
+            .await
+            .map_err(|_| anyhow!("No response within {:?}", self.timeout))??
+            .error_for_status()?;
+
+        info!("Streaming {} Pyth feeds from {}", feed_ids.len(), self.base_url);
+
+        let mut body = response.bytes_stream();
+        let mut buffer = String::new();
+        // Hermes sends updates and keep-alives continuously; a silent stream is treated as dead
+        loop {
+            let chunk = match tokio::time::timeout(self.timeout, body.next()).await {
+                Ok(Some(chunk)) => chunk?,
+                Ok(None) => return Ok(()),
+                Err(_) => return Err(anyhow!("No stream data within {:?}", self.timeout)),
+            };
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            self.apply_events(&mut buffer);
+        }
+    }
+
+    // Applies each complete SSE event in the buffer, leaving a partial one for the next chunk.
+    // Events are separated by a blank line; only `data:` lines carry payload.
+    fn apply_events(&self, buffer: &mut String) {
+        while let Some(end) = buffer.find("\n\n") {
+            let event: String = buffer.drain(..end + 2).collect();
+            for data in event.lines().filter_map(|line| line.strip_prefix("data:")) {
+                match serde_json::from_str(data.trim()) {
+                    Ok(response) => {
+                        if let Err(e) = self.apply(response) {
+                            warn!("Rejected Hermes update: {}", e);
+                        }
+                    }
+                    Err(e) => warn!("Malformed Hermes event: {}", e),
+                }
+            }
+        }
+    }
+
+    fn query(feed_ids: &[FeedId]) -> Vec<(&'static str, String)> {
+        let mut query: Vec<_> = feed_ids.iter().map(|id| ("ids[]", hex::encode(id))).collect();
+        query.push(("encoding", "hex".to_string()));
+        // Prices are taken from the verified binary payload, never from Hermes' parsed JSON
+        query.push(("parsed", "false".to_string()));
+        query
+    }
+
+    fn apply(&self, response: HermesResponse) -> Result<()> {
+        if response.binary.encoding != "hex" {
+            return Err(anyhow!("Unexpected Hermes encoding {}", response.binary.encoding));
+        }
+
+        for data in response.binary.data {
+            let messages = verify_update(&hex::decode(data)?, &self.guardians)?;
+
+            let mut latest = self.latest.write().unwrap();
+            for message in messages {
+                // Out-of-order events must not overwrite a newer price
+                let newer = latest
+                    .get(&message.feed_id)
+                    .is_none_or(|current| message.publish_time > current.publish_time);
+                if newer {
+                    latest.insert(message.feed_id, message);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pyth_accumulator::tests::{guardians, LATEST_FIXTURE, SOL_FEED_ID, STREAM_FIXTURE};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    fn sol_feed_id() -> FeedId {
+        hex::decode(SOL_FEED_ID).unwrap().try_into().unwrap()
+    }
+
+    fn client() -> HermesClient {
+        client_for("http://127.0.0.1:0".to_string())
+    }
+
+    fn client_for(base_url: String) -> HermesClient {
+        HermesClient::new(base_url, guardians(), Duration::from_millis(500)).unwrap()
+    }
+
+    fn sse(body: &str) -> String {
+        format!("HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n{}", body)
+    }
+
+    fn json(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    // Answers one request on a local port with `response`, then keeps the connection open for `hold`.
+    // Returns the base URL and the request line.
+    async fn stub(response: String, hold: Duration) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let request_line = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 4096];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = socket.read(&mut chunk).await.unwrap();
+                assert!(read > 0, "connection closed mid-request");
+                request.extend_from_slice(&chunk[..read]);
+            }
+            socket.write_all(response.as_bytes()).await.unwrap();
+            tokio::time::sleep(hold).await;
+            String::from_utf8_lossy(&request).lines().next().unwrap().to_string()
+        });
+
+        (url, request_line)
+    }
+
+    #[test]
+    fn applies_stream_events_split_across_chunks() {
+        let client = client();
+        let mut buffer = String::new();
+
+        // Chunk boundaries fall inside events and inside the blank line between them
+        for chunk in STREAM_FIXTURE.as_bytes().chunks(97) {
+            buffer.push_str(std::str::from_utf8(chunk).unwrap());
+            client.apply_events(&mut buffer);
+        }
+
+        assert!(buffer.is_empty());
+        // The stream replays an older update after a newer one; the newer price is kept
+        let sol = client.latest_price(&sol_feed_id()).unwrap();
+        assert_eq!((sol.price, sol.publish_time), (14_241_000_000, 1_760_000_001));
+        assert_eq!(client.latest.read().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn keeps_a_partial_event_until_it_completes() {
+        let client = client();
+        let first_event = STREAM_FIXTURE.find("\n\n").unwrap() + 2;
+        let mut buffer = STREAM_FIXTURE[..first_event - 1].to_string();
+
+        client.apply_events(&mut buffer);
+        assert!(client.latest_price(&sol_feed_id()).is_none());
+
+        buffer.push('\n');
+        client.apply_events(&mut buffer);
+        assert!(client.latest_price(&sol_feed_id()).is_some());
+    }
+
+    #[test]
+    fn skips_tampered_and_malformed_events() {
+        let client = client();
+        let tampered = LATEST_FIXTURE.trim().replacen("0000000000000000", "0000000000000001", 1);
+        let mut buffer = format!("data:{}\n\ndata:{{not json\n\n: keep-alive\n\n", tampered);
+
+        client.apply_events(&mut buffer);
+
+        assert!(buffer.is_empty());
+        assert!(client.latest.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn applies_the_latest_fixture() {
+        let client = client();
+        client.apply(serde_json::from_str(LATEST_FIXTURE).unwrap()).unwrap();
+
+        let sol = client.latest_price(&sol_feed_id()).unwrap();
+        assert_eq!((sol.price, sol.expo, sol.publish_time), (14_235_120_000, -8, 1_760_000_000));
+    }
+
+    #[tokio::test]
+    async fn fetch_latest_requests_binary_updates_and_applies_them() {
+        let (url, request_line) = stub(json("200 OK", LATEST_FIXTURE), Duration::ZERO).await;
+        let client = client_for(url);
+
+        client.fetch_latest(&[sol_feed_id()]).await.unwrap();
+
+        let request_line = request_line.await.unwrap();
+        assert!(request_line.starts_with("GET /v2/updates/price/latest?"), "{request_line}");
+        assert!(request_line.contains(&format!("ids%5B%5D={}", SOL_FEED_ID)));
+        assert!(request_line.contains("encoding=hex&parsed=false"));
+        let sol = client.latest_price(&sol_feed_id()).unwrap();
+        assert_eq!((sol.price, sol.publish_time), (14_235_120_000, 1_760_000_000));
+    }
+
+    #[tokio::test]
+    async fn fetch_latest_fails_on_error_status_and_timeout() {
+        let (url, _) = stub(json("503 Service Unavailable", "{}"), Duration::ZERO).await;
+        assert!(client_for(url).fetch_latest(&[sol_feed_id()]).await.is_err());
+
+        // Headers never arrive
+        let (url, stalled) = stub(String::new(), Duration::from_secs(5)).await;
+        let error = client_for(url).fetch_latest(&[sol_feed_id()]).await.unwrap_err();
+        assert!(error.chain().any(|cause| cause.to_string().contains("timed out")), "{error:?}");
+        stalled.abort();
+    }
+
+    #[tokio::test]
+    async fn stream_once_applies_events_until_the_stream_ends() {
+        let (url, request_line) = stub(sse(STREAM_FIXTURE), Duration::ZERO).await;
+        let client = client_for(url);
+
+        client.stream_once(&[sol_feed_id()]).await.unwrap();
+
+        assert!(request_line.await.unwrap().starts_with("GET /v2/updates/price/stream?"));
+        let sol = client.latest_price(&sol_feed_id()).unwrap();
+        assert_eq!((sol.price, sol.publish_time), (14_241_000_000, 1_760_000_001));
+        assert_eq!(client.latest.read().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn stream_once_gives_up_on_a_silent_stream() {
+        let first_event = STREAM_FIXTURE.find("\n\n").unwrap() + 2;
+        let (url, stalled) = stub(sse(&STREAM_FIXTURE[..first_event]), Duration::from_secs(5)).await;
+        let client = client_for(url);
+
+        let error = client.stream_once(&[sol_feed_id()]).await.unwrap_err();
+
+        assert!(error.to_string().starts_with("No stream data within"), "{error}");
+        assert!(client.latest_price(&sol_feed_id()).is_some());
+        stalled.abort();
+    }
+}
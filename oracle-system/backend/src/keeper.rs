@@ -0,0 +1,376 @@
+use crate::database::Database;
+use crate::oracle_manager::OracleManager;
+use crate::oracle_program::{self, PriceData, PriceSource};
+use crate::price_aggregator::ConsensusPrice;
+use crate::rpc_pool::RpcPool;
+use anyhow::{bail, Result};
+use log::{error, info, warn};
+use oracle_core::consensus;
+use oracle_core::FixedPrice;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+// publish_price plus init_if_needed on first use, with headroom
+const COMPUTE_UNIT_LIMIT: u32 = 60_000;
+const CONFIRMATION_POLL: Duration = Duration::from_millis(500);
+
+// When the keeper pushes a symbol's consensus price on-chain
+#[derive(Debug, Clone)]
+pub struct PushPolicy {
+    // Push at least this often while prices are available
+    pub heartbeat: Duration,
+    // Push early when the price moves this far from the last pushed one
+    pub deviation_bps: u64,
+    // Cap on the compute unit price, in micro-lamports
+    pub max_priority_fee: u64,
+    // Sends with a fresh blockhash before giving up on a price
+    pub max_attempts: u32,
+}
+
+struct Pushed {
+    price: FixedPrice,
+    at: Instant,
+}
+
+// Publishes consensus prices to the oracle program's publish_price instruction
+pub struct Keeper {
+    rpc_pool: Arc<RpcPool>,
+    database: Arc<Database>,
+    manager: Arc<OracleManager>,
+    payer: Arc<Keypair>,
+    program_id: Pubkey,
+    policy: PushPolicy,
+    last_pushed: Mutex<HashMap<String, Pushed>>,
+    in_flight: Mutex<HashSet<String>>,
+}
+
+impl Keeper {
+    pub fn new(
+        rpc_pool: Arc<RpcPool>,
+        database: Arc<Database>,
+        manager: Arc<OracleManager>,
+        payer: Keypair,
+        program_id: Pubkey,
+        policy: PushPolicy,
+    ) -> Self {
+        Self {
+            rpc_pool,
+            database,
+            manager,
+            payer: Arc::new(payer),
+            program_id,
+            policy,
+            last_pushed: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    // Checks every symbol with an oracle_account once per interval; each due push runs in its own task
+    // so one slow confirmation doesn't hold up the others. On shutdown, returns once in-flight pushes
+    // have confirmed or failed.
+    pub async fn run(self: Arc<Self>, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval(interval);
+        let mut pushes = JoinSet::new();
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                Some(result) = pushes.join_next() => {
+                    if let Err(e) = result {
+                        error!("Keeper push task failed: {}", e);
+                    }
+                    continue;
+                }
+                _ = shutdown.changed() => break,
+            }
+
+            // Followers leave pushing to the leader, like every other write
+            if !self.manager.is_leader() {
+                continue;
+            }
+
+            for config in self.manager.configs() {
+                let Some(oracle_account) = config.oracle_account else {
+                    continue;
+                };
+                if self.in_flight.lock().unwrap().contains(&config.symbol) {
+                    continue;
+                }
+
+                let price = match self.database.get_cached_price(&config.symbol).await {
+                    Ok(Some(price)) => price,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        error!(
+                            "Keeper failed to read cached price for {}: {}",
+                            config.symbol, e
+                        );
+                        continue;
+                    }
+                };
+                if !self.is_due(&config.symbol, price.price) {
+                    continue;
+                }
+
+                self.in_flight.lock().unwrap().insert(config.symbol.clone());
+                let keeper = self.clone();
+                pushes.spawn(async move {
+                    match keeper.push(oracle_account, &price).await {
+                        Ok(signature) => {
+                            info!(
+                                "Pushed {} {} on-chain: {}",
+                                config.symbol, price.price, signature
+                            );
+                            keeper.last_pushed.lock().unwrap().insert(
+                                config.symbol.clone(),
+                                Pushed {
+                                    price: price.price,
+                                    at: Instant::now(),
+                                },
+                            );
+                        }
+                        Err(e) => error!("Failed to push {} on-chain: {}", config.symbol, e),
+                    }
+                    keeper.in_flight.lock().unwrap().remove(&config.symbol);
+                });
+            }
+        }
+
+        // A transaction already sent may still land, so wait for its outcome rather than dropping it
+        if !pushes.is_empty() {
+            info!("Waiting for {} in-flight keeper pushes", pushes.len());
+        }
+        while let Some(result) = pushes.join_next().await {
+            if let Err(e) = result {
+                error!("Keeper push task failed: {}", e);
+            }
+        }
+    }
+
+    fn is_due(&self, symbol: &str, price: FixedPrice) -> bool {
+        is_due(
+            &self.policy,
+            self.last_pushed.lock().unwrap().get(symbol),
+            price,
+        )
+    }
+
+    // Sends until confirmed, re-signing with a fresh blockhash whenever the previous one expires
+    async fn push(&self, oracle_account: Pubkey, price: &ConsensusPrice) -> Result<Signature> {
+        let published_price =
+            oracle_program::published_price_address(&self.program_id, &oracle_account);
+        let price_data = PriceData::from_fixed(
+            price.price,
+            price.confidence,
+            price.timestamp,
+            PriceSource::Internal,
+        )?;
+        let publish = oracle_program::publish_price(
+            self.program_id,
+            oracle_account,
+            self.payer.pubkey(),
+            &price_data,
+        );
+        let priority_fee = self.estimate_priority_fee(&[published_price]).await;
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+            ComputeBudgetInstruction::set_compute_unit_price(priority_fee),
+            publish,
+        ];
+
+        for attempt in 1..=self.policy.max_attempts {
+            let commitment = self.rpc_pool.commitment();
+            let (blockhash, last_valid_block_height) = self
+                .rpc_pool
+                .execute(|client| async move {
+                    client
+                        .get_latest_blockhash_with_commitment(commitment)
+                        .await
+                })
+                .await?;
+            let transaction = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&self.payer.pubkey()),
+                &[self.payer.as_ref()],
+                blockhash,
+            );
+
+            let send_config = RpcSendTransactionConfig {
+                preflight_commitment: Some(commitment.commitment),
+                ..RpcSendTransactionConfig::default()
+            };
+            let signature = self
+                .rpc_pool
+                .execute(|client| {
+                    let transaction = transaction.clone();
+                    async move {
+                        client
+                            .send_transaction_with_config(&transaction, send_config)
+                            .await
+                    }
+                })
+                .await?;
+
+            if self.confirm(&signature, last_valid_block_height).await? {
+                return Ok(signature);
+            }
+            warn!(
+                "Push of {} expired unconfirmed (attempt {}/{})",
+                signature, attempt, self.policy.max_attempts
+            );
+        }
+
+        bail!("Not confirmed after {} attempts", self.policy.max_attempts)
+    }
+
+    // True once the signature reaches the pool's commitment, false if its blockhash expires first
+    async fn confirm(&self, signature: &Signature, last_valid_block_height: u64) -> Result<bool> {
+        let commitment = self.rpc_pool.commitment();
+
+        loop {
+            tokio::time::sleep(CONFIRMATION_POLL).await;
+
+            let signatures = [*signature];
+            let statuses = self
+                .rpc_pool
+                .execute(|client| async move { client.get_signature_statuses(&signatures).await })
+                .await?;
+            if let Some(Some(status)) = statuses.value.first() {
+                if let Some(err) = &status.err {
+                    bail!("Transaction {} failed: {}", signature, err);
+                }
+                if status.satisfies_commitment(commitment) {
+                    return Ok(true);
+                }
+            }
+
+            let block_height = self
+                .rpc_pool
+                .execute(|client| async move {
+                    client.get_block_height_with_commitment(commitment).await
+                })
+                .await?;
+            if block_height > last_valid_block_height {
+                return Ok(false);
+            }
+        }
+    }
+
+    async fn estimate_priority_fee(&self, writable: &[Pubkey]) -> u64 {
+        let fees = self
+            .rpc_pool
+            .execute(|client| async move { client.get_recent_prioritization_fees(writable).await })
+            .await;
+
+        match fees {
+            Ok(fees) => priority_fee(
+                fees.into_iter().map(|fee| fee.prioritization_fee).collect(),
+                self.policy.max_priority_fee,
+            ),
+            Err(e) => {
+                warn!("Priority fee estimate failed, sending without one: {}", e);
+                0
+            }
+        }
+    }
+}
+
+// Due on the first push, once the heartbeat elapses, or when the price moves deviation_bps from the last push
+fn is_due(policy: &PushPolicy, last: Option<&Pushed>, price: FixedPrice) -> bool {
+    let Some(last) = last else {
+        return true;
+    };
+    if last.at.elapsed() >= policy.heartbeat {
+        return true;
+    }
+
+    let expo = consensus::common_expo(&[price, last.price]);
+    match (
+        consensus::mantissa_at(&price, expo),
+        consensus::mantissa_at(&last.price, expo),
+    ) {
+        (Ok(current), Ok(previous)) => {
+            consensus::deviation_bps(current, previous) >= policy.deviation_bps as u128
+        }
+        _ => true,
+    }
+}
+
+// 75th percentile of recent fees paid for the written account, capped by the policy
+fn priority_fee(mut fees: Vec<u64>, max_priority_fee: u64) -> u64 {
+    fees.sort_unstable();
+    let estimate = fees.get(fees.len() * 3 / 4).copied().unwrap_or(0);
+    estimate.min(max_priority_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PushPolicy {
+        PushPolicy {
+            heartbeat: Duration::from_secs(60),
+            deviation_bps: 50,
+            max_priority_fee: 10_000,
+            max_attempts: 3,
+        }
+    }
+
+    fn pushed(mantissa: i64, ago: Duration) -> Pushed {
+        Pushed {
+            price: FixedPrice::new(mantissa, -2),
+            at: Instant::now() - ago,
+        }
+    }
+
+    #[test]
+    fn first_push_is_always_due() {
+        assert!(is_due(&policy(), None, FixedPrice::new(10_000, -2)));
+    }
+
+    #[test]
+    fn push_is_due_once_the_heartbeat_elapses() {
+        let last = pushed(10_000, Duration::from_secs(61));
+        assert!(is_due(&policy(), Some(&last), FixedPrice::new(10_000, -2)));
+
+        let last = pushed(10_000, Duration::from_secs(30));
+        assert!(!is_due(&policy(), Some(&last), FixedPrice::new(10_000, -2)));
+    }
+
+    #[test]
+    fn push_is_due_early_at_the_deviation_threshold() {
+        let last = pushed(10_000, Duration::from_secs(1));
+
+        // 49 and 50 bps from the last push, compared at the finer exponent
+        assert!(!is_due(
+            &policy(),
+            Some(&last),
+            FixedPrice::new(100_490, -3)
+        ));
+        assert!(is_due(&policy(), Some(&last), FixedPrice::new(100_500, -3)));
+        assert!(is_due(&policy(), Some(&last), FixedPrice::new(9_950, -2)));
+    }
+
+    #[test]
+    fn priority_fee_takes_the_75th_percentile() {
+        assert_eq!(priority_fee(Vec::new(), 10_000), 0);
+        assert_eq!(priority_fee(vec![500], 10_000), 500);
+        // Index 3 of the sorted four
+        assert_eq!(priority_fee(vec![400, 100, 300, 200], 10_000), 400);
+        let fees = (1..=100).rev().collect();
+        assert_eq!(priority_fee(fees, 10_000), 76);
+    }
+
+    #[test]
+    fn priority_fee_is_capped_by_the_policy() {
+        assert_eq!(priority_fee(vec![0, 0, 50_000, 90_000], 10_000), 10_000);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::database::Database;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use log::{info, warn, error};
+
+const LEADER_KEY: &str = "leader:oracle";
+
+// Where the lease is held; Redis in production
+#[async_trait]
+pub trait LeaseStore: Send + Sync {
+    async fn acquire_lease(&self, key: &str, holder: &str, lease: Duration) -> Result<bool>;
+    async fn renew_lease(&self, key: &str, holder: &str, lease: Duration) -> Result<bool>;
+    async fn release_lease(&self, key: &str, holder: &str) -> Result<()>;
+}
+
+#[async_trait]
+impl LeaseStore for Database {
+    async fn acquire_lease(&self, key: &str, holder: &str, lease: Duration) -> Result<bool> {
+        Database::acquire_lease(self, key, holder, lease).await
+    }
+
+    async fn renew_lease(&self, key: &str, holder: &str, lease: Duration) -> Result<bool> {
+        Database::renew_lease(self, key, holder, lease).await
+    }
+
+    async fn release_lease(&self, key: &str, holder: &str) -> Result<()> {
+        Database::release_lease(self, key, holder).await
+    }
+}
+
+// Redis lease deciding which replica publishes prices and writes history. Followers keep computing
+// consensus so a takeover only waits for the old lease to expire.
+pub struct LeaderElection {
+    store: Arc<dyn LeaseStore>,
+    instance_id: String,
+    lease: Duration,
+    // Local end of the lease, measured from before the Redis call that granted it, so a stalled
+    // renewal stops this instance writing before another one can take over
+    leader_until: Mutex<Option<Instant>>,
+}
+
+impl LeaderElection {
+    pub fn new(store: Arc<dyn LeaseStore>, instance_id: String, lease: Duration) -> Self {
+        Self {
+            store,
+            instance_id,
+            lease,
+            leader_until: Mutex::new(None),
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leader_until.lock().unwrap().is_some_and(|until| Instant::now() < until)
+    }
+
+    // Acquires or renews the lease every third of its length until `drained` fires, then releases it
+    // so a follower can take over immediately. Shutdown must only signal `drained` once the final
+    // writes and pushes are done, so the lease keeps covering them.
+    pub async fn run(self: Arc<Self>, mut drained: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval(self.lease / 3);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = drained.changed() => break,
+            }
+
+            let was_leader = self.is_leader();
+            let started = Instant::now();
+            let held = if was_leader {
+                self.store.renew_lease(LEADER_KEY, &self.instance_id, self.lease).await
+            } else {
+                self.store.acquire_lease(LEADER_KEY, &self.instance_id, self.lease).await
+            };
+
+            let held = match held {
+                Ok(held) => held,
+                Err(e) => {
+                    // Without Redis the lease can't be confirmed; stand down rather than risk two leaders
+                    error!("Leader lease check failed: {}", e);
+                    false
+                }
+            };
+
+            *self.leader_until.lock().unwrap() = held.then(|| started + self.lease);
+            match (was_leader, held) {
+                (false, true) => info!("{} became leader", self.instance_id),
+                (true, false) => warn!("{} lost leadership", self.instance_id),
+                _ => {}
+            }
+        }
+
+        if self.is_leader() {
+            *self.leader_until.lock().unwrap() = None;
+            match self.store.release_lease(LEADER_KEY, &self.instance_id).await {
+                Ok(()) => info!("{} released leadership", self.instance_id),
+                Err(e) => error!("Failed to release leader lease: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Grants every lease and records each call in order
+    #[derive(Default)]
+    struct RecordingStore {
+        calls: Mutex<Vec<&'static str>>,
+    }
+
+    #[async_trait]
+    impl LeaseStore for RecordingStore {
+        async fn acquire_lease(&self, _key: &str, _holder: &str, _lease: Duration) -> Result<bool> {
+            self.calls.lock().unwrap().push("acquire");
+            Ok(true)
+        }
+
+        async fn renew_lease(&self, _key: &str, _holder: &str, _lease: Duration) -> Result<bool> {
+            self.calls.lock().unwrap().push("renew");
+            Ok(true)
+        }
+
+        async fn release_lease(&self, _key: &str, _holder: &str) -> Result<()> {
+            self.calls.lock().unwrap().push("release");
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_renewing_until_drained_then_releases() {
+        let store = Arc::new(RecordingStore::default());
+        let election = Arc::new(LeaderElection::new(store.clone(), "test".to_string(), Duration::from_millis(60)));
+        let (drained_tx, drained_rx) = watch::channel(false);
+        let task = tokio::spawn(election.clone().run(drained_rx));
+
+        // Several renewals while the rest of the process drains
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(election.is_leader());
+        {
+            let calls = store.calls.lock().unwrap();
+            assert_eq!(calls[0], "acquire");
+            assert!(calls.iter().filter(|call| **call == "renew").count() >= 2);
+            assert!(!calls.contains(&"release"));
+        }
+
+        drained_tx.send(true).unwrap();
+        task.await.unwrap();
+
+        assert!(!election.is_leader());
+        assert_eq!(store.calls.lock().unwrap().last(), Some(&"release"));
+    }
+}
@@ -1,60 +1,303 @@
+mod account_fetcher;
+mod account_subscriber;
+mod rpc_pool;
+mod hermes_client;
+mod pyth_accumulator;
 mod pyth_client;
 mod switchboard_client;
 mod oracle_manager;
 mod price_aggregator;
 mod database;
+mod metrics;
+mod health;
+mod alerting;
+mod alert_sinks;
+mod leader_election;
+mod oracle_program;
+mod keeper;
+mod reconciliation;
+mod disagreement_resolver;
+mod symbol_config;
 mod api;
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Signer};
 use dotenv::dotenv;
 use std::env;
-use solana_sdk::pubkey::Pubkey;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+use log::{info, error};
+
+// How long in-flight updates, requests and pool connections get to finish on shutdown
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Pythnet accumulator emitter that signs every Hermes update
+const PYTH_ACCUMULATOR_EMITTER: &str = "e101faedac5851e32b9b23b5f9411a8c2bac4aae3ed4dd7b811dd1a72ea4aa71";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
     env_logger::init();
 
-    let rpc_url = env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
+    // SOLANA_RPC_URLS takes a comma-separated list of providers; SOLANA_RPC_URL remains the single-endpoint form
+    let rpc_urls: Vec<String> = env::var("SOLANA_RPC_URLS")
+        .or_else(|_| env::var("SOLANA_RPC_URL"))
+        .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string())
+        .split(',')
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
     let pg_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let redis_url = env::var("REDIS_URL").expect("REDIS_URL must be set");
 
-    let rpc_client = Arc::new(RpcClient::new(rpc_url));
-    
-    let pyth_client = Arc::new(pyth_client::PythClient::new(rpc_client.clone()));
-    let switchboard_client = Arc::new(switchboard_client::SwitchboardClient::new(rpc_client.clone()));
-    let aggregator = Arc::new(price_aggregator::PriceAggregator::new(100)); // 1% max deviation (100 bps)
-    let database = Arc::new(database::Database::new(&pg_url, &redis_url).await?);
+    let rpc_timeout = env::var("SOLANA_RPC_TIMEOUT_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(Duration::from_secs(5));
+    let commitment = env::var("SOLANA_COMMITMENT")
+        .ok()
+        .and_then(|c| CommitmentConfig::from_str(&c).ok())
+        .unwrap_or_else(CommitmentConfig::confirmed);
+
+    // Every RPC call carries this timeout, so a hung node can't stall a tick indefinitely
+    let rpc_pool = Arc::new(rpc_pool::RpcPool::new(rpc_urls, rpc_timeout, commitment)?);
+    tokio::spawn(rpc_pool.clone().run_health_checks(Duration::from_secs(5)));
+
+    // "subscription" streams feed accounts over websocket; anything else polls
+    let update_mode = match env::var("ORACLE_UPDATE_MODE").as_deref() {
+        Ok("subscription") => oracle_manager::UpdateMode::Subscription {
+            ws_url: env::var("SOLANA_WS_URL").unwrap_or_else(|_| rpc_pool.primary_url().replacen("http", "ws", 1)),
+            idle_timeout: env::var("SOLANA_WS_IDLE_TIMEOUT_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(30)),
+        },
+        _ => oracle_manager::UpdateMode::Polling,
+    };
+
+    let account_fetcher = Arc::new(account_fetcher::AccountFetcher::new(rpc_pool.clone()));
+    // "hermes" reads Pyth from the Hermes price service instead of Solana accounts
+    let pyth_client = match env::var("PYTH_SOURCE").as_deref() {
+        Ok("hermes") => {
+            let guardians = pyth_accumulator::GuardianSet::from_hex(
+                env::var("WORMHOLE_GUARDIAN_SET_INDEX").ok().and_then(|s| s.parse().ok()).unwrap_or(0),
+                &env::var("WORMHOLE_GUARDIANS").expect("WORMHOLE_GUARDIANS must be set when PYTH_SOURCE=hermes"),
+                &env::var("PYTH_EMITTER_ADDRESS").unwrap_or_else(|_| PYTH_ACCUMULATOR_EMITTER.to_string()),
+            )?;
+            let hermes_url = env::var("HERMES_URL").unwrap_or_else(|_| "https://hermes.pyth.network".to_string());
+            // Bounds one-shot fetches and the silence tolerated on the stream before reconnecting
+            let hermes_timeout = env::var("HERMES_TIMEOUT_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(10);
+            let hermes = Arc::new(hermes_client::HermesClient::new(hermes_url, guardians, Duration::from_secs(hermes_timeout))?);
+            Arc::new(pyth_client::PythClient::with_hermes(hermes))
+        }
+        _ => Arc::new(pyth_client::PythClient::new()),
+    };
+    let switchboard_client = Arc::new(switchboard_client::SwitchboardClient::new());
+    let max_slot_lag = env::var("MAX_SLOT_LAG").ok().and_then(|s| s.parse().ok()).unwrap_or(10);
+    let max_clock_skew = env::var("MAX_CLOCK_SKEW_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(5);
+    let mut aggregator = price_aggregator::PriceAggregator::new(max_slot_lag, max_clock_skew);
+    if let Some(sigma) = env::var("CONFIDENCE_SIGMA").ok().and_then(|s| s.parse().ok()) {
+        aggregator.set_overlap_sigma(sigma);
+    }
+    let aggregator = Arc::new(aggregator);
+    let metrics = Arc::new(metrics::Metrics::new());
+    let database = Arc::new(database::Database::new(&pg_url, &redis_url, metrics.clone()).await?);
     
     // Run migrations
     database.migrate().await?;
 
+    // Per-source priors for resolving two-source disagreements, e.g. "Pyth:0.7,Switchboard:0.5"
+    let reliability = source_map("SOURCE_RELIABILITY");
+    let resolver = Arc::new(disagreement_resolver::DisagreementResolver::new(database.clone(), reliability, 300, 0.15));
+
     let mut oracle_manager = oracle_manager::OracleManager::new(
+        account_fetcher,
         pyth_client,
         switchboard_client,
         aggregator,
+        resolver,
         database.clone(),
+        metrics.clone(),
     );
+    oracle_manager.set_update_mode(update_mode);
 
-    // Example Config - SOL/USD
-    // Replace with actual feed IDs for Devnet/Mainnet
-    oracle_manager.add_config(oracle_manager::OracleConfig {
-        symbol: "SOL".to_string(),
-        pyth_feed: Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap(), // Devnet SOL/USD (Placeholder)
-        switchboard_feed: Pubkey::from_str("GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR").unwrap(), // Example
-    });
+    // Flipped once on SIGINT/SIGTERM; the API and the update loop both wind down from it
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+    // With several replicas, only the holder of the Redis lease publishes and writes history. The lease
+    // is held until the drain below finishes, then released.
+    let (drained_tx, drained_rx) = watch::channel(false);
+    let mut leader_task = None;
+    if env::var("LEADER_ELECTION").is_ok_and(|v| v == "redis") {
+        let instance_id = env::var("INSTANCE_ID").unwrap_or_else(|_| {
+            let host = env::var("HOSTNAME").unwrap_or_else(|_| "oracle".to_string());
+            format!("{}-{}", host, std::process::id())
+        });
+        let lease = env::var("LEADER_LEASE_MS")
+            .ok()
+            .and_then(|ms| ms.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(Duration::from_secs(5));
+        let leader_election = Arc::new(leader_election::LeaderElection::new(database.clone(), instance_id, lease));
+        info!("Leader election enabled as {}", leader_election.instance_id());
+        leader_task = Some(tokio::spawn(leader_election.clone().run(drained_rx)));
+        oracle_manager.set_leader_election(leader_election);
+    }
+
+    // Rule breaches and recoveries go to every sink in ALERT_SINKS, e.g. "log,slack=https://hooks.slack.com/..."
+    let sinks = alert_sinks::parse_sinks(
+        &env::var("ALERT_SINKS").unwrap_or_else(|_| "log".to_string()),
+        &env::var("PAGERDUTY_EVENTS_URL").unwrap_or_else(|_| alert_sinks::PAGERDUTY_EVENTS_URL.to_string()),
+    )?;
+    let rules = alerting::AlertRules {
+        source_down_secs: env::var("ALERT_SOURCE_DOWN_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(60),
+        deviation_bps: env::var("ALERT_DEVIATION_BPS").ok().and_then(|s| s.parse().ok()).unwrap_or(100),
+        stale_secs: env::var("ALERT_STALE_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(30),
+    };
+    let alert_engine = Arc::new(alerting::AlertEngine::new(rules, sinks));
+    oracle_manager.set_alerts(alert_engine.clone());
+
+    // Symbols, feeds and per-symbol settings; edits to the file are applied without a restart
+    let symbols_path = PathBuf::from(env::var("SYMBOLS_CONFIG").unwrap_or_else(|_| "symbols.toml".to_string()));
+    let oracle_manager = Arc::new(oracle_manager);
+    tokio::spawn(alert_engine.clone().run(oracle_manager.clone(), Duration::from_secs(1), shutdown_rx.clone()));
+    // Admin API changes persisted in Postgres are layered over the file
+    let registry = Arc::new(symbol_config::SymbolRegistry::new(oracle_manager.clone(), database.clone()));
+    registry.load(symbol_config::load(&symbols_path)?).await?;
+    tokio::spawn(symbol_config::watch(symbols_path, registry.clone(), Duration::from_secs(5)));
+
+    // Publishes consensus prices on-chain for symbols with an oracle_account, signed by this keypair
+    let program_id = env::var("ORACLE_PROGRAM_ID").ok().map(|id| Pubkey::from_str(&id)).transpose()?;
+    let mut keeper_pubkey = None;
+    let mut keeper_task = None;
+    if let Ok(keypair_path) = env::var("KEEPER_KEYPAIR") {
+        let payer = read_keypair_file(&keypair_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read KEEPER_KEYPAIR {}: {}", keypair_path, e))?;
+        let program_id = program_id.expect("ORACLE_PROGRAM_ID must be set with KEEPER_KEYPAIR");
+        let policy = keeper::PushPolicy {
+            heartbeat: Duration::from_secs(env::var("KEEPER_HEARTBEAT_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(60)),
+            deviation_bps: env::var("KEEPER_DEVIATION_BPS").ok().and_then(|s| s.parse().ok()).unwrap_or(50),
+            max_priority_fee: env::var("KEEPER_MAX_PRIORITY_FEE").ok().and_then(|s| s.parse().ok()).unwrap_or(100_000),
+            max_attempts: env::var("KEEPER_MAX_ATTEMPTS").ok().and_then(|s| s.parse().ok()).unwrap_or(3),
+        };
+        keeper_pubkey = Some(payer.pubkey());
+        let keeper = Arc::new(keeper::Keeper::new(
+            rpc_pool.clone(),
+            database.clone(),
+            oracle_manager.clone(),
+            payer,
+            program_id,
+            policy,
+        ));
+        info!("Keeper publishing to program {}", program_id);
+        keeper_task = Some(tokio::spawn(keeper.run(Duration::from_secs(1), shutdown_rx.clone())));
+    }
+
+    // Simulates the program's checks against cached prices; the fee payer only has to exist on-chain
+    let fee_payer = env::var("RECONCILE_FEE_PAYER").ok().map(|key| Pubkey::from_str(&key)).transpose()?.or(keeper_pubkey);
+    if let (Some(program_id), Some(fee_payer)) = (program_id, fee_payer) {
+        let mut monitor = reconciliation::ReconciliationMonitor::new(
+            rpc_pool.clone(),
+            database.clone(),
+            oracle_manager.clone(),
+            metrics.clone(),
+            program_id,
+            fee_payer,
+            env::var("RECONCILE_ALERT_BPS").ok().and_then(|s| s.parse().ok()).unwrap_or(50),
+        );
+        monitor.set_alerts(alert_engine.clone());
+        let monitor = Arc::new(monitor);
+        let interval = env::var("RECONCILE_INTERVAL_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(30);
+        tokio::spawn(monitor.run(Duration::from_secs(interval), shutdown_rx.clone()));
+    }
+
+    // Readiness counts an endpoint as usable within MAX_SLOT_LAG; liveness fails after HEALTH_MAX_IDLE_SECS without a tick
+    let max_idle = env::var("HEALTH_MAX_IDLE_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(60);
+    let health = Arc::new(health::HealthChecker::new(
+        database.clone(),
+        rpc_pool.clone(),
+        oracle_manager.clone(),
+        max_slot_lag,
+        max_idle,
+    ));
 
     // Spawn API Server
     let db_clone = database.clone();
-    tokio::spawn(async move {
-        api::start_api_server(db_clone).await;
+    let admin_token = env::var("ADMIN_API_TOKEN").ok().filter(|token| !token.is_empty());
+    let mut api_shutdown = shutdown_rx.clone();
+    let api_server = tokio::spawn(async move {
+        let shutdown = async move {
+            let _ = api_shutdown.changed().await;
+        };
+        api::start_api_server(db_clone, registry, metrics, rpc_pool, health, admin_token, shutdown).await;
     });
 
     // Start Oracle Loop
     println!("Starting Oracle Service...");
-    oracle_manager.start_price_loop().await;
+    let price_loop = tokio::spawn(oracle_manager.start_price_loop(shutdown_rx));
+
+    shutdown_signal().await;
+    info!("Shutdown requested, finishing in-flight updates");
+    let _ = shutdown_tx.send(true);
+
+    // Bounded so a hung RPC or database call can't keep the process alive indefinitely
+    let drained = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+        let _ = price_loop.await;
+        let _ = api_server.await;
+        if let Some(keeper_task) = keeper_task {
+            let _ = keeper_task.await;
+        }
+        // Releasing the lease lets a follower take over without waiting for it to expire
+        let _ = drained_tx.send(true);
+        if let Some(leader_task) = leader_task {
+            let _ = leader_task.await;
+        }
+        database.close().await;
+    })
+    .await;
+
+    match drained {
+        Ok(()) => {
+            info!("Shutdown complete");
+            Ok(())
+        }
+        Err(_) => Err(anyhow::anyhow!("Shutdown timed out after {:?}; pending writes may be lost", SHUTDOWN_TIMEOUT)),
+    }
+}
+
+async fn shutdown_signal() {
+    let terminate = async {
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to install SIGTERM handler: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate => {}
+    }
+}
 
-    Ok(())
+// Parses "Source:value,Source:value" pairs, skipping malformed entries
+fn source_map<T: FromStr>(var: &str) -> HashMap<String, T> {
+    env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| entry.split_once(':'))
+        .filter_map(|(source, value)| Some((source.trim().to_string(), value.trim().parse().ok()?)))
+        .collect()
 }
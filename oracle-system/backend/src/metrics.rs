@@ -0,0 +1,324 @@
+use crate::rpc_pool::EndpointStatus;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Prometheus' default latency buckets in seconds, extended below 5ms for account decoding
+const LATENCY_BUCKETS: [f64; 15] = [0.0001, 0.00025, 0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+// Label values in the order of the family's label names
+type LabelValues = Vec<String>;
+
+struct Family<T> {
+    name: &'static str,
+    help: &'static str,
+    labels: &'static [&'static str],
+    series: Mutex<BTreeMap<LabelValues, T>>,
+}
+
+impl<T: Default> Family<T> {
+    fn new(name: &'static str, help: &'static str, labels: &'static [&'static str]) -> Self {
+        Self { name, help, labels, series: Mutex::new(BTreeMap::new()) }
+    }
+
+    fn with(&self, values: &[&str], f: impl FnOnce(&mut T)) {
+        debug_assert_eq!(values.len(), self.labels.len(), "{} label count", self.name);
+        let mut series = self.series.lock().unwrap();
+        f(series.entry(values.iter().map(|v| v.to_string()).collect()).or_default());
+    }
+
+    fn header(&self, out: &mut String, kind: &str) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} {}", self.name, kind);
+    }
+}
+
+#[derive(Default)]
+struct Histogram {
+    // Non-cumulative counts per bucket; made cumulative when rendered
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+type Counter = Family<u64>;
+type Gauge = Family<f64>;
+
+impl Counter {
+    fn inc(&self, values: &[&str]) {
+        self.with(values, |count| *count += 1);
+    }
+
+    fn render(&self, out: &mut String) {
+        self.header(out, "counter");
+        for (values, count) in self.series.lock().unwrap().iter() {
+            let _ = writeln!(out, "{}{} {}", self.name, label_set(self.labels, values, None), count);
+        }
+    }
+}
+
+impl Gauge {
+    fn set(&self, values: &[&str], value: f64) {
+        self.with(values, |gauge| *gauge = value);
+    }
+
+    fn render(&self, out: &mut String) {
+        self.header(out, "gauge");
+        for (values, value) in self.series.lock().unwrap().iter() {
+            let _ = writeln!(out, "{}{} {}", self.name, label_set(self.labels, values, None), value);
+        }
+    }
+}
+
+impl Family<Histogram> {
+    fn observe(&self, values: &[&str], elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        self.with(values, |histogram| {
+            if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| secs <= *bound) {
+                histogram.buckets[bucket] += 1;
+            }
+            histogram.count += 1;
+            histogram.sum += secs;
+        });
+    }
+
+    fn render(&self, out: &mut String) {
+        self.header(out, "histogram");
+        for (values, histogram) in self.series.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let le = bound.to_string();
+                let _ = writeln!(out, "{}_bucket{} {}", self.name, label_set(self.labels, values, Some(&le)), cumulative);
+            }
+            let _ = writeln!(out, "{}_bucket{} {}", self.name, label_set(self.labels, values, Some("+Inf")), histogram.count);
+            let _ = writeln!(out, "{}_sum{} {}", self.name, label_set(self.labels, values, None), histogram.sum);
+            let _ = writeln!(out, "{}_count{} {}", self.name, label_set(self.labels, values, None), histogram.count);
+        }
+    }
+}
+
+// {name="value",...}, with the histogram bucket bound appended as le
+fn label_set(names: &[&str], values: &[String], le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// Counters, gauges and histograms served in the Prometheus text format at /metrics
+pub struct Metrics {
+    fetch_duration: Family<Histogram>,
+    fetch_failures: Counter,
+    source_decode_duration: Family<Histogram>,
+    source_errors: Counter,
+    source_age: Gauge,
+    source_deviation: Gauge,
+    source_rejections: Counter,
+    consensus_updates: Counter,
+    consensus_failures: Counter,
+    store_write_duration: Family<Histogram>,
+    store_write_errors: Counter,
+    reconciliation_divergence: Gauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            fetch_duration: Family::new(
+                "oracle_fetch_duration_seconds",
+                "Time a symbol waited for its feed accounts, read in one getMultipleAccounts batch with every other symbol due at the same time",
+                &["symbol"],
+            ),
+            fetch_failures: Family::new(
+                "oracle_fetch_failures_total",
+                "Feed account reads that failed or timed out",
+                &["symbol"],
+            ),
+            source_decode_duration: Family::new(
+                "oracle_source_decode_duration_seconds",
+                "Time to decode each source's price from its fetched account or Hermes update",
+                &["symbol", "source"],
+            ),
+            source_errors: Family::new(
+                "oracle_source_errors_total",
+                "Source prices that could not be read or decoded",
+                &["symbol", "source"],
+            ),
+            source_age: Family::new(
+                "oracle_source_age_seconds",
+                "Age of the latest price read from each source",
+                &["symbol", "source"],
+            ),
+            source_deviation: Family::new(
+                "oracle_source_deviation_bps",
+                "Deviation of each contributing source from the median, in basis points",
+                &["symbol", "source"],
+            ),
+            source_rejections: Family::new(
+                "oracle_source_rejections_total",
+                "Sources excluded from consensus, by reason",
+                &["symbol", "source", "reason"],
+            ),
+            consensus_updates: Family::new(
+                "oracle_consensus_updates_total",
+                "Consensus prices published",
+                &["symbol"],
+            ),
+            consensus_failures: Family::new(
+                "oracle_consensus_failures_total",
+                "Rounds that produced no consensus price, by reason",
+                &["symbol", "reason"],
+            ),
+            store_write_duration: Family::new(
+                "oracle_store_write_duration_seconds",
+                "Redis and Postgres write latency",
+                &["store", "operation"],
+            ),
+            store_write_errors: Family::new(
+                "oracle_store_write_errors_total",
+                "Failed Redis and Postgres writes",
+                &["store", "operation"],
+            ),
+            reconciliation_divergence: Family::new(
+                "oracle_reconciliation_divergence_bps",
+                "Divergence of the oracle program's simulated result from the cached price",
+                &["symbol", "check"],
+            ),
+        }
+    }
+
+    pub fn record_fetch(&self, symbol: &str, elapsed: Duration) {
+        self.fetch_duration.observe(&[symbol], elapsed);
+    }
+
+    pub fn record_fetch_failure(&self, symbol: &str) {
+        self.fetch_failures.inc(&[symbol]);
+    }
+
+    pub fn record_decode(&self, symbol: &str, source: &str, elapsed: Duration) {
+        self.source_decode_duration.observe(&[symbol, source], elapsed);
+    }
+
+    pub fn record_source_error(&self, symbol: &str, source: &str) {
+        self.source_errors.inc(&[symbol, source]);
+    }
+
+    pub fn record_source_age(&self, symbol: &str, source: &str, age_secs: i64) {
+        self.source_age.set(&[symbol, source], age_secs as f64);
+    }
+
+    pub fn record_source_deviation(&self, symbol: &str, source: &str, deviation_bps: u64) {
+        self.source_deviation.set(&[symbol, source], deviation_bps as f64);
+    }
+
+    pub fn record_rejection(&self, symbol: &str, source: &str, reason: &str) {
+        self.source_rejections.inc(&[symbol, source, reason]);
+    }
+
+    pub fn record_consensus(&self, symbol: &str) {
+        self.consensus_updates.inc(&[symbol]);
+    }
+
+    pub fn record_consensus_failure(&self, symbol: &str, reason: &str) {
+        self.consensus_failures.inc(&[symbol, reason]);
+    }
+
+    pub fn record_write<T, E>(&self, store: &str, operation: &str, elapsed: Duration, result: &Result<T, E>) {
+        self.store_write_duration.observe(&[store, operation], elapsed);
+        if result.is_err() {
+            self.store_write_errors.inc(&[store, operation]);
+        }
+    }
+
+    pub fn record_divergence(&self, symbol: &str, check: &str, divergence_bps: u64) {
+        self.reconciliation_divergence.set(&[symbol, check], divergence_bps as f64);
+    }
+
+    // RPC endpoint health is read from the pool at scrape time rather than mirrored here
+    pub fn render(&self, endpoints: &[EndpointStatus]) -> String {
+        let mut out = String::new();
+        self.fetch_duration.render(&mut out);
+        self.fetch_failures.render(&mut out);
+        self.source_decode_duration.render(&mut out);
+        self.source_errors.render(&mut out);
+        self.source_age.render(&mut out);
+        self.source_deviation.render(&mut out);
+        self.source_rejections.render(&mut out);
+        self.consensus_updates.render(&mut out);
+        self.consensus_failures.render(&mut out);
+        self.store_write_duration.render(&mut out);
+        self.store_write_errors.render(&mut out);
+        self.reconciliation_divergence.render(&mut out);
+
+        // Keyed by position in SOLANA_RPC_URLS, since several endpoints can share a provider's host
+        let labels = &["endpoint", "host"];
+        let latency = Gauge::new("oracle_rpc_latency_ms", "Moving average of successful RPC call latency", labels);
+        let error_rate = Gauge::new("oracle_rpc_error_rate", "Moving average of the RPC error rate, 0 to 1", labels);
+        let slot_lag = Gauge::new("oracle_rpc_slot_lag", "Slots behind the freshest endpoint at the last health check", labels);
+        for endpoint in endpoints {
+            let index = endpoint.index.to_string();
+            let values = [index.as_str(), endpoint.endpoint.as_str()];
+            latency.set(&values, endpoint.latency_ms);
+            error_rate.set(&values, endpoint.error_rate);
+            slot_lag.set(&values, endpoint.slot_lag as f64);
+        }
+        latency.render(&mut out);
+        error_rate.render(&mut out);
+        slot_lag.render(&mut out);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(index: usize, latency_ms: f64) -> EndpointStatus {
+        EndpointStatus {
+            index,
+            endpoint: "rpc.example.com".to_string(),
+            latency_ms,
+            error_rate: 0.0,
+            slot: 100,
+            slot_lag: 0,
+        }
+    }
+
+    #[test]
+    fn endpoints_on_the_same_host_stay_distinct() {
+        let out = Metrics::new().render(&[endpoint(0, 12.5), endpoint(1, 80.0)]);
+
+        assert!(out.contains("oracle_rpc_latency_ms{endpoint=\"0\",host=\"rpc.example.com\"} 12.5\n"));
+        assert!(out.contains("oracle_rpc_latency_ms{endpoint=\"1\",host=\"rpc.example.com\"} 80\n"));
+    }
+
+    #[test]
+    fn histograms_render_cumulative_buckets() {
+        let metrics = Metrics::new();
+        metrics.record_decode("SOL", "Pyth", Duration::from_micros(200));
+        metrics.record_decode("SOL", "Pyth", Duration::from_millis(3));
+        let out = metrics.render(&[]);
+
+        let labels = "symbol=\"SOL\",source=\"Pyth\"";
+        assert!(out.contains(&format!("oracle_source_decode_duration_seconds_bucket{{{},le=\"0.00025\"}} 1\n", labels)));
+        assert!(out.contains(&format!("oracle_source_decode_duration_seconds_bucket{{{},le=\"0.005\"}} 2\n", labels)));
+        assert!(out.contains(&format!("oracle_source_decode_duration_seconds_bucket{{{},le=\"+Inf\"}} 2\n", labels)));
+        assert!(out.contains(&format!("oracle_source_decode_duration_seconds_count{{{}}} 2\n", labels)));
+    }
+}
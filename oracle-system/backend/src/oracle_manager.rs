@@ -1,99 +1,633 @@
+use crate::alerting::AlertEngine;
+use crate::account_fetcher::{AccountFetcher, FeedAccount};
+use crate::account_subscriber::{AccountSubscriber, AccountUpdate};
+use crate::pyth_accumulator::FeedId;
+use crate::hermes_client::HermesClient;
 use crate::pyth_client::PythClient;
 use crate::switchboard_client::SwitchboardClient;
-use crate::price_aggregator::PriceAggregator;
+use crate::disagreement_resolver::{DisagreementResolver, Resolution, HISTORY_REFRESH_INTERVAL};
+use crate::price_aggregator::{
+    AggregationError, AggregationStrategy, ConsensusPrice, PriceAggregator, QuorumPolicy, RejectedSource,
+    RejectionReason, SourcePrice, SymbolStatus, Thresholds,
+};
 use crate::database::Database;
+use crate::leader_election::LeaderElection;
+use crate::metrics::Metrics;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use serde::{Serialize, Deserialize};
 use solana_sdk::pubkey::Pubkey;
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use log::{info, error};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::{self, JoinHandle, JoinSet};
+use log::{info, warn, error};
 
-#[derive(Clone)]
+// How often the supervisor checks for update tasks that stopped
+const SUPERVISOR_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct OracleConfig {
     pub symbol: String,
     pub pyth_feed: Pubkey,
+    // Pyth price feed ID, used instead of pyth_feed when Pyth is read through Hermes
+    pub pyth_feed_id: Option<FeedId>,
     pub switchboard_feed: Pubkey,
+    // Oracle program config account the keeper publishes this symbol's consensus price under
+    pub oracle_account: Option<Pubkey>,
+    pub aggregation: AggregationStrategy,
+    pub quorum: QuorumPolicy,
+    pub thresholds: Thresholds,
+    pub schedule: Schedule,
+}
+
+// When a symbol is polled
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schedule {
+    pub interval_ms: u64,
+    // How long the symbol waits on a batched fetch of its feed accounts, including RPC failover
+    pub fetch_timeout_ms: u64,
+    pub missed_ticks: MissedTicks,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            interval_ms: 500, // Sub-second updates
+            fetch_timeout_ms: 3000,
+            missed_ticks: MissedTicks::Skip,
+        }
+    }
+}
+
+// When a symbol is next due after a round that started late, e.g. because the previous one overran
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedTicks {
+    // Poll back to back until the missed intervals are made up
+    Burst,
+    // Restart the interval from the late round
+    Delay,
+    // Drop the missed intervals and stay on the original cadence
+    Skip,
+}
+
+#[derive(Clone, Debug)]
+pub enum UpdateMode {
+    // Symbols are polled on their own intervals, with every symbol due at the same time read in one batch
+    Polling,
+    // Push updates from accountSubscribe on the given websocket URL, polling only while it is down.
+    // The socket is reopened when no notification arrives within idle_timeout.
+    Subscription { ws_url: String, idle_timeout: Duration },
 }
 
 pub struct OracleManager {
+    account_fetcher: Arc<AccountFetcher>,
     pyth_client: Arc<PythClient>,
     switchboard_client: Arc<SwitchboardClient>,
     aggregator: Arc<PriceAggregator>,
+    resolver: Arc<DisagreementResolver>,
     database: Arc<Database>,
-    configs: HashMap<String, OracleConfig>,
+    metrics: Arc<Metrics>,
+    configs: RwLock<HashMap<String, OracleConfig>>,
+    // Signalled whenever the symbol set changes so feed subscriptions can be rebuilt
+    config_changes: watch::Sender<()>,
+    update_mode: UpdateMode,
+    subscriber: Option<Arc<AccountSubscriber>>,
+    // Latest subscribed account data, and the pubkey of each change for the symbol tasks
+    account_cache: RwLock<HashMap<Pubkey, FeedAccount>>,
+    // Highest context slot the subscription has reported for any feed
+    subscription_slot: AtomicU64,
+    account_updates: broadcast::Sender<Pubkey>,
+    // Unset for a single instance, which always publishes
+    leader_election: Option<Arc<LeaderElection>>,
+    alerts: Option<Arc<AlertEngine>>,
+    // Unix time a symbol task last finished a tick, fetched or not, 0 before the first; read by the liveness check
+    last_round: AtomicI64,
 }
 
 impl OracleManager {
     pub fn new(
+        account_fetcher: Arc<AccountFetcher>,
         pyth_client: Arc<PythClient>,
         switchboard_client: Arc<SwitchboardClient>,
         aggregator: Arc<PriceAggregator>,
+        resolver: Arc<DisagreementResolver>,
         database: Arc<Database>,
+        metrics: Arc<Metrics>,
     ) -> Self {
         Self {
+            account_fetcher,
             pyth_client,
             switchboard_client,
             aggregator,
+            resolver,
             database,
-            configs: HashMap::new(),
+            metrics,
+            configs: RwLock::new(HashMap::new()),
+            config_changes: watch::channel(()).0,
+            update_mode: UpdateMode::Polling,
+            subscriber: None,
+            account_cache: RwLock::new(HashMap::new()),
+            subscription_slot: AtomicU64::new(0),
+            account_updates: broadcast::channel(1024).0,
+            leader_election: None,
+            alerts: None,
+            last_round: AtomicI64::new(0),
+        }
+    }
+
+    // Swaps in a full set of symbol configs, e.g. after the config file changes
+    pub fn replace_configs(&self, configs: HashMap<String, OracleConfig>) {
+        let mut current = self.configs.write().unwrap();
+        for symbol in current.keys().filter(|symbol| !configs.contains_key(*symbol)) {
+            info!("Removing symbol {}", symbol);
+        }
+        for (symbol, config) in &configs {
+            match current.get(symbol) {
+                None => info!("Adding symbol {}", symbol),
+                Some(existing) if existing != config => info!("Updating symbol {}", symbol),
+                Some(_) => {}
+            }
+        }
+
+        if *current != configs {
+            *current = configs;
+            drop(current);
+            self.config_changes.send_replace(());
+        }
+    }
+
+    pub fn configs(&self) -> Vec<OracleConfig> {
+        self.configs.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn set_update_mode(&mut self, update_mode: UpdateMode) {
+        self.subscriber = match &update_mode {
+            UpdateMode::Polling => None,
+            UpdateMode::Subscription { ws_url, idle_timeout } => {
+                Some(Arc::new(AccountSubscriber::new(ws_url.clone(), self.account_fetcher.commitment(), *idle_timeout)))
+            }
+        };
+        self.update_mode = update_mode;
+    }
+
+    pub fn set_leader_election(&mut self, leader_election: Arc<LeaderElection>) {
+        self.leader_election = Some(leader_election);
+    }
+
+    pub fn set_alerts(&mut self, alerts: Arc<AlertEngine>) {
+        self.alerts = Some(alerts);
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leader_election.as_ref().is_none_or(|election| election.is_leader())
+    }
+
+    pub fn last_round(&self) -> Option<i64> {
+        Some(self.last_round.load(Ordering::SeqCst)).filter(|at| *at > 0)
+    }
+
+    // Runs until shutdown is signalled, then returns once in-flight updates have finished writing
+    pub async fn start_price_loop(self: Arc<Self>, shutdown: watch::Receiver<bool>) {
+        if let Some(hermes) = self.pyth_client.hermes() {
+            tokio::spawn(self.clone().run_hermes_stream(hermes));
+        }
+        if let Some(subscriber) = self.subscriber.clone() {
+            tokio::spawn(self.clone().run_subscription(subscriber));
+        }
+
+        tokio::spawn(self.clone().run_history_refresh(shutdown.clone()));
+        let poller = tokio::spawn(self.clone().run_poller(shutdown.clone()));
+        self.supervise(shutdown).await;
+        if let Err(e) = poller.await {
+            error!("Poller failed during shutdown: {}", e);
+        }
+    }
+
+    // Wakes whenever a symbol is due and reads the feed accounts of every due symbol in one batched
+    // fetch. Each symbol's round then runs in its own task, waiting on the batch for at most its own
+    // fetch_timeout_ms, so a slow decode or write only delays that symbol. A symbol whose previous
+    // round is still running is not due again until it finishes.
+    async fn run_poller(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let mut changes = self.config_changes.subscribe();
+        let mut next_due: HashMap<String, Instant> = HashMap::new();
+        let mut rounds = JoinSet::new();
+        let mut in_flight: HashMap<task::Id, String> = HashMap::new();
+
+        loop {
+            let configs = self.configs.read().unwrap().clone();
+            let now = Instant::now();
+            next_due.retain(|symbol, _| configs.contains_key(symbol));
+            for symbol in configs.keys() {
+                next_due.entry(symbol.clone()).or_insert(now);
+            }
+
+            let busy: HashSet<String> = in_flight.values().cloned().collect();
+            let due: Vec<&OracleConfig> = configs
+                .values()
+                .filter(|config| next_due[&config.symbol] <= now && !busy.contains(&config.symbol))
+                .collect();
+
+            // Subscribed feeds arrive as updates; only poll while the subscription is down
+            if !due.is_empty() && !self.subscription_connected() {
+                let batch = self.fetch_batch(&due);
+                for config in &due {
+                    let manager = self.clone();
+                    let config = (*config).clone();
+                    let batch = batch.clone();
+                    let symbol = config.symbol.clone();
+                    let round = rounds.spawn(async move { manager.poll_symbol(&config, batch).await });
+                    in_flight.insert(round.id(), symbol);
+                }
+            }
+            for config in &due {
+                let schedule = &config.schedule;
+                let at = next_due.get_mut(&config.symbol).unwrap();
+                *at = next_poll(*at, now, Duration::from_millis(schedule.interval_ms), schedule.missed_ticks);
+            }
+
+            // Symbols still running are woken by their round finishing instead
+            let wake = next_due
+                .iter()
+                .filter(|(symbol, _)| !in_flight.values().any(|running| running == *symbol))
+                .map(|(_, at)| *at)
+                .min()
+                .unwrap_or(now + SUPERVISOR_INTERVAL);
+
+            tokio::select! {
+                _ = tokio::time::sleep_until(wake.into()) => {}
+                Some(result) = rounds.join_next_with_id() => {
+                    let id = match &result {
+                        Ok((id, ())) => *id,
+                        Err(e) => e.id(),
+                    };
+                    if let (Some(symbol), Err(e)) = (in_flight.remove(&id), result) {
+                        error!("Update round for {} failed: {}", symbol, e);
+                    }
+                }
+                _ = changes.changed() => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+
+        // An update already underway completes its writes
+        info!("Waiting for {} in-flight update rounds", rounds.len());
+        while let Some(result) = rounds.join_next().await {
+            if let Err(e) = result {
+                error!("Update round failed during shutdown: {}", e);
+            }
         }
     }
 
-    pub fn add_config(&mut self, config: OracleConfig) {
-        self.configs.insert(config.symbol.clone(), config);
+    // One getMultipleAccounts read of every due symbol's feeds, shared by their rounds. It runs in
+    // its own task, so symbols that stop waiting don't cancel it for the others.
+    fn fetch_batch(self: &Arc<Self>, due: &[&OracleConfig]) -> FetchBatch {
+        let mut seen = HashSet::new();
+        let pubkeys: Vec<Pubkey> = due
+            .iter()
+            .flat_map(|config| self.symbol_pubkeys(config))
+            .filter(|key| seen.insert(*key))
+            .collect();
+
+        let manager = self.clone();
+        let fetch = tokio::spawn(async move {
+            let accounts = manager.account_fetcher.fetch_accounts(&pubkeys).await?;
+            if manager.subscriber.is_some() {
+                manager.account_cache.write().unwrap().extend(accounts.clone());
+            }
+            Ok(Arc::new(accounts))
+        });
+
+        let started = Instant::now();
+        let batch = async move {
+            match fetch.await {
+                Ok(result) => result.map_err(|e: anyhow::Error| Arc::new(e.to_string())),
+                Err(e) => Err(Arc::new(e.to_string())),
+            }
+        };
+        FetchBatch { started, accounts: batch.boxed().shared() }
     }
 
-    pub async fn start_price_loop(&self) {
-        let interval_duration = std::time::Duration::from_millis(500); // Sub-second updates
-        let mut interval = tokio::time::interval(interval_duration);
+    // Keeps one update task per enabled symbol: starts new symbols, restarts changed ones with their
+    // new config, stops removed ones and restarts any task that panicked or exited
+    async fn supervise(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let mut changes = self.config_changes.subscribe();
+        let mut check = tokio::time::interval(SUPERVISOR_INTERVAL);
+        let mut tasks: HashMap<String, (OracleConfig, JoinHandle<()>)> = HashMap::new();
 
         loop {
-            interval.tick().await;
-            for (symbol, config) in &self.configs {
-                self.update_price(symbol, config).await;
+            // Tasks that returned because of shutdown must not be mistaken for crashes
+            if *shutdown.borrow() {
+                break;
+            }
+
+            let configs = self.configs.read().unwrap().clone();
+
+            tasks.retain(|symbol, (config, task)| {
+                let current = configs.get(symbol) == Some(config);
+                if !current {
+                    task.abort();
+                }
+                current
+            });
+
+            for (symbol, (config, task)) in tasks.iter_mut() {
+                if task.is_finished() {
+                    let restarted = tokio::spawn(self.clone().run_symbol(config.clone(), shutdown.clone()));
+                    let stopped = std::mem::replace(task, restarted);
+                    match stopped.await {
+                        Err(e) if e.is_panic() => error!("Update task for {} panicked, restarting", symbol),
+                        _ => warn!("Update task for {} exited, restarting", symbol),
+                    }
+                }
+            }
+
+            for (symbol, config) in configs {
+                tasks.entry(symbol).or_insert_with(|| {
+                    let task = tokio::spawn(self.clone().run_symbol(config.clone(), shutdown.clone()));
+                    (config, task)
+                });
+            }
+
+            tokio::select! {
+                _ = changes.changed() => {}
+                _ = check.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+
+        // Symbol tasks stop at their next wait, so an update already underway completes its writes
+        info!("Stopping {} update tasks", tasks.len());
+        for (symbol, (_, task)) in tasks {
+            if let Err(e) = task.await {
+                error!("Update task for {} failed during shutdown: {}", symbol, e);
+            }
+        }
+    }
+
+    // Updates one symbol on every change to its feeds while subscribed
+    async fn run_symbol(self: Arc<Self>, config: OracleConfig, mut shutdown: watch::Receiver<bool>) {
+        let pubkeys = self.symbol_pubkeys(&config);
+        let mut updates = self.account_updates.subscribe();
+
+        loop {
+            tokio::select! {
+                update = updates.recv() => {
+                    // A lagged receiver missed some pubkeys, so refresh from the cache regardless
+                    if !matches!(update, Ok(pubkey) if !pubkeys.contains(&pubkey)) {
+                        let accounts = current_as_of(&self.account_cache.read().unwrap(), self.subscription_slot.load(Ordering::SeqCst));
+                        self.update_price(&config.symbol, &config, &accounts).await;
+                        self.last_round.store(chrono::Utc::now().timestamp(), Ordering::SeqCst);
+                    }
+                }
+                _ = shutdown.changed() => return,
+            }
+        }
+    }
+
+    // One polled round: waits on the shared batch for up to the symbol's fetch_timeout_ms, then updates
+    async fn poll_symbol(&self, config: &OracleConfig, batch: FetchBatch) {
+        let fetch_timeout = Duration::from_millis(config.schedule.fetch_timeout_ms);
+        match tokio::time::timeout(fetch_timeout, batch.accounts).await {
+            Ok(Ok(accounts)) => {
+                self.metrics.record_fetch(&config.symbol, batch.started.elapsed());
+                self.update_price(&config.symbol, config, &accounts).await;
+            }
+            Ok(Err(e)) => {
+                self.metrics.record_fetch_failure(&config.symbol);
+                error!("Failed to fetch feed accounts for {}: {}", config.symbol, e);
+            }
+            Err(_) => {
+                self.metrics.record_fetch_failure(&config.symbol);
+                warn!("Fetching feed accounts for {} timed out after {:?}", config.symbol, fetch_timeout);
+            }
+        }
+
+        // The loop is alive whatever the outcome; failed rounds surface through readiness and alerts
+        self.last_round.store(chrono::Utc::now().timestamp(), Ordering::SeqCst);
+    }
+
+    fn subscription_connected(&self) -> bool {
+        self.subscriber
+            .as_ref()
+            .is_some_and(|subscriber| subscriber.connected().load(Ordering::SeqCst))
+    }
+
+    // Keeps the resolver's agreement history current, off the update path
+    async fn run_history_refresh(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval_at((Instant::now() + HISTORY_REFRESH_INTERVAL).into(), HISTORY_REFRESH_INTERVAL);
+        let mut changes = self.config_changes.subscribe();
+
+        loop {
+            let symbols: Vec<String> = self.configs.read().unwrap().keys().cloned().collect();
+            self.resolver.refresh(&symbols).await;
+
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = changes.changed() => {}
+                _ = shutdown.changed() => return,
             }
         }
     }
 
-    async fn update_price(&self, symbol: &str, config: &OracleConfig) {
-        // Fetch from Pyth
-        let pyth_res = self.pyth_client.get_price_data(&config.pyth_feed).await;
-        
-        // Fetch from Switchboard
-        let sb_res = self.switchboard_client.get_price_data(&config.switchboard_feed).await;
+    // Streams the configured feed IDs, restarting the stream when the set of IDs changes
+    async fn run_hermes_stream(self: Arc<Self>, hermes: Arc<HermesClient>) {
+        let mut changes = self.config_changes.subscribe();
+
+        loop {
+            let feed_ids = self.hermes_feed_ids();
+            let stream = tokio::spawn(hermes.clone().run_stream(feed_ids.clone()));
+
+            while self.hermes_feed_ids() == feed_ids {
+                if changes.changed().await.is_err() {
+                    return;
+                }
+            }
+            stream.abort();
+        }
+    }
+
+    fn hermes_feed_ids(&self) -> Vec<FeedId> {
+        let mut feed_ids: Vec<FeedId> = self.configs().iter().filter_map(|config| config.pyth_feed_id).collect();
+        feed_ids.sort_unstable();
+        feed_ids.dedup();
+        feed_ids
+    }
+
+    // Feeds the account cache from accountSubscribe, resubscribing when the set of feeds changes
+    async fn run_subscription(self: Arc<Self>, subscriber: Arc<AccountSubscriber>) {
+        let connected = subscriber.connected();
+        let (tx, mut rx) = mpsc::channel::<AccountUpdate>(1024);
+        let mut changes = self.config_changes.subscribe();
+
+        let mut pubkeys = self.feed_pubkeys();
+        let mut subscriber_task = tokio::spawn(subscriber.clone().run(pubkeys.clone(), tx.clone()));
+
+        // Seed the cache so every symbol has data before its first notification arrives
+        self.seed_account_cache().await;
+
+        loop {
+            tokio::select! {
+                Some(update) = rx.recv() => {
+                    self.subscription_slot.fetch_max(update.account.slot, Ordering::SeqCst);
+                    self.account_cache.write().unwrap().insert(update.pubkey, update.account);
+                    let _ = self.account_updates.send(update.pubkey);
+                }
+                Ok(()) = changes.changed() => {
+                    let updated = self.feed_pubkeys();
+                    if updated == pubkeys {
+                        continue;
+                    }
+
+                    // Feed set changed: resubscribe and seed the new feeds
+                    info!("Symbol configs changed, resubscribing to {} feeds", updated.len());
+                    subscriber_task.abort();
+                    connected.store(false, Ordering::SeqCst);
+                    pubkeys = updated;
+                    subscriber_task = tokio::spawn(subscriber.clone().run(pubkeys.clone(), tx.clone()));
+                    self.seed_account_cache().await;
+                }
+            }
+        }
+    }
+
+    // One batched fetch for every configured feed instead of one get_account per feed
+    async fn seed_account_cache(&self) {
+        match self.account_fetcher.fetch_accounts(&self.feed_pubkeys()).await {
+            Ok(accounts) => {
+                let slot = accounts.values().map(|feed| feed.slot).max().unwrap_or(0);
+                self.subscription_slot.fetch_max(slot, Ordering::SeqCst);
+                *self.account_cache.write().unwrap() = accounts;
+            }
+            Err(e) => error!("Failed to fetch feed accounts: {}", e),
+        }
+    }
+
+    fn feed_pubkeys(&self) -> Vec<Pubkey> {
+        let mut seen = HashSet::new();
+        self.configs()
+            .iter()
+            .flat_map(|config| self.symbol_pubkeys(config))
+            .filter(|key| seen.insert(*key))
+            .collect()
+    }
+
+    fn symbol_pubkeys(&self, config: &OracleConfig) -> Vec<Pubkey> {
+        // Hermes-backed Pyth feeds don't need their on-chain account
+        let pyth_feed = (!self.reads_hermes(config)).then_some(config.pyth_feed);
+        pyth_feed.into_iter().chain([config.switchboard_feed]).collect()
+    }
+
+    fn reads_hermes(&self, config: &OracleConfig) -> bool {
+        self.pyth_client.hermes().is_some() && config.pyth_feed_id.is_some()
+    }
+
+    async fn update_price(&self, symbol: &str, config: &OracleConfig, accounts: &HashMap<Pubkey, FeedAccount>) {
+        // Decode Pyth, either from the on-chain account or the latest verified Hermes update
+        let started = Instant::now();
+        let pyth_res = match config.pyth_feed_id.filter(|_| self.reads_hermes(config)) {
+            Some(feed_id) => {
+                // Off-chain updates have no Solana context slot; treat them as current
+                let slot = accounts.values().map(|feed| feed.slot).max().unwrap_or(0);
+                self.pyth_client
+                    .get_hermes_price_data(&feed_id)
+                    .map(|data| (data.price, data.confidence, data.publish_time, slot))
+                    .map_err(anyhow::Error::from)
+            }
+            None => accounts
+                .get(&config.pyth_feed)
+                .ok_or_else(|| anyhow::anyhow!("Pyth account {} not found", config.pyth_feed))
+                .and_then(|feed| {
+                    let data = self.pyth_client.get_price_data(&config.pyth_feed, &feed.account)?;
+                    Ok((data.price, data.confidence, data.publish_time, feed.slot))
+                }),
+        };
+        self.metrics.record_decode(symbol, "Pyth", started.elapsed());
+
+        // Decode Switchboard
+        let started = Instant::now();
+        let sb_res = accounts
+            .get(&config.switchboard_feed)
+            .ok_or_else(|| anyhow::anyhow!("Switchboard account {} not found", config.switchboard_feed))
+            .and_then(|feed| {
+                let (price, conf, ts) = self.switchboard_client.get_price_data(&config.switchboard_feed, &feed.account)?;
+                Ok((price, conf, ts, feed.slot))
+            });
+        self.metrics.record_decode(symbol, "Switchboard", started.elapsed());
 
         let mut prices = Vec::new();
+        let mut fetch_errors = Vec::new();
 
-        if let Ok((price, conf, ts)) = pyth_res {
-            prices.push(crate::price_aggregator::SourcePrice {
+        match pyth_res {
+            Ok((price, conf, ts, slot)) => prices.push(SourcePrice {
                 price,
                 confidence: conf,
                 timestamp: ts,
+                slot,
                 source: "Pyth".to_string(),
-            });
-        } else {
-            error!("Failed to fetch Pyth price for {}", symbol);
+            }),
+            Err(e) => {
+                error!("Failed to fetch Pyth price for {}: {}", symbol, e);
+                self.metrics.record_source_error(symbol, "Pyth");
+                fetch_errors.push(RejectedSource {
+                    source: "Pyth".to_string(),
+                    price: None,
+                    reason: RejectionReason::FetchError { error: e.to_string() },
+                });
+            }
         }
 
-        if let Ok((price, conf, ts)) = sb_res {
-            prices.push(crate::price_aggregator::SourcePrice {
+        match sb_res {
+            Ok((price, conf, ts, slot)) => prices.push(SourcePrice {
                 price,
                 confidence: conf,
                 timestamp: ts,
+                slot,
                 source: "Switchboard".to_string(),
-            });
-        } else {
-            error!("Failed to fetch Switchboard price for {}", symbol);
+            }),
+            Err(e) => {
+                error!("Failed to fetch Switchboard price for {}: {}", symbol, e);
+                self.metrics.record_source_error(symbol, "Switchboard");
+                fetch_errors.push(RejectedSource {
+                    source: "Switchboard".to_string(),
+                    price: None,
+                    reason: RejectionReason::FetchError { error: e.to_string() },
+                });
+            }
         }
 
-        if prices.is_empty() {
-            error!("No price sources available for {}", symbol);
-            return;
+        if let Some(alerts) = &self.alerts {
+            let read: Vec<&str> = prices.iter().map(|price| price.source.as_str()).collect();
+            alerts.observe_sources(symbol, &read);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        for price in &prices {
+            self.metrics.record_source_age(symbol, &price.source, now - price.timestamp);
         }
 
         // Aggregate
-        match self.aggregator.calculate_consensus(prices.clone()) {
-            Ok(consensus_price) => {
+        let result = match self.aggregator.calculate_consensus(prices, &config.aggregation, &config.quorum, &config.thresholds) {
+            Err(AggregationError::Disagreement(candidates)) => self.resolve_disagreement(symbol, config, candidates).await,
+            other => other,
+        };
+
+        self.record_round(symbol, &result);
+        if let Some(alerts) = &self.alerts {
+            alerts.observe_round(symbol, &result);
+        }
+
+        // Followers compute the same consensus so they can take over at once, but only the leader publishes
+        if !self.is_leader() {
+            return;
+        }
+
+        match result {
+            Ok(mut consensus_price) => {
+                consensus_price.rejected.extend(fetch_errors);
                 info!("Updated price for {}: {}", symbol, consensus_price.price);
                 
                 // Cache in Redis
@@ -106,9 +640,184 @@ impl OracleManager {
                     error!("Failed to save history: {}", e);
                 }
             }
+            Err(AggregationError::InsufficientQuorum(mut failure)) => {
+                failure.rejected.extend(fetch_errors);
+                error!(
+                    "Insufficient quorum for {}: {} of {} sources, missing required {:?}",
+                    symbol, failure.sources_used, failure.min_sources, failure.missing_required
+                );
+                self.withhold_price(symbol, SymbolStatus::InsufficientQuorum(failure)).await;
+            }
+            Err(AggregationError::Unhealthy(report)) => {
+                error!("{} marked unhealthy: {}", symbol, report.reason);
+                self.withhold_price(symbol, SymbolStatus::Unhealthy(report)).await;
+            }
             Err(e) => {
                 error!("Consensus failed for {}: {}", symbol, e);
             }
         }
     }
+
+    // Every replica records its own rounds, whether or not it publishes them
+    fn record_round(&self, symbol: &str, result: &Result<ConsensusPrice, AggregationError>) {
+        let rejected = match result {
+            Ok(consensus_price) => {
+                self.metrics.record_consensus(symbol);
+                self.resolver.observe_consensus(symbol, consensus_price.price);
+                for source in &consensus_price.sources {
+                    self.metrics.record_source_deviation(symbol, &source.source, source.deviation_bps);
+                }
+                consensus_price.rejected.as_slice()
+            }
+            Err(AggregationError::InsufficientQuorum(failure)) => {
+                self.metrics.record_consensus_failure(symbol, "insufficient_quorum");
+                failure.rejected.as_slice()
+            }
+            Err(AggregationError::Unhealthy(_)) => {
+                self.metrics.record_consensus_failure(symbol, "unhealthy");
+                &[]
+            }
+            Err(_) => {
+                self.metrics.record_consensus_failure(symbol, "error");
+                &[]
+            }
+        };
+        for rejection in rejected {
+            self.metrics.record_rejection(symbol, &rejection.source, rejection.reason.kind());
+        }
+    }
+
+    // Two sources disagree: trust the one the resolver picks, or declare the symbol unhealthy
+    async fn resolve_disagreement(
+        &self,
+        symbol: &str,
+        config: &OracleConfig,
+        candidates: Vec<SourcePrice>,
+    ) -> Result<ConsensusPrice, AggregationError> {
+        let trusted = match self.resolver.resolve(symbol, &candidates) {
+            Resolution::Trust { source, scores } => {
+                warn!("Sources for {} disagree, trusting {} (scores {:?})", symbol, source, scores);
+                source
+            }
+            Resolution::Unhealthy(report) => return Err(AggregationError::Unhealthy(report)),
+        };
+
+        let (kept, dropped): (Vec<_>, Vec<_>) = candidates.into_iter().partition(|p| p.source == trusted);
+        let mut consensus_price = self.aggregator.calculate_consensus(kept, &config.aggregation, &config.quorum, &config.thresholds)?;
+        consensus_price.rejected.extend(dropped.into_iter().map(|p| RejectedSource {
+            source: p.source,
+            price: Some(p.price),
+            reason: RejectionReason::Disagreement { trusted: trusted.clone() },
+        }));
+        Ok(consensus_price)
+    }
+
+    // Withdraw the cached price so consumers see why instead of a degraded price
+    async fn withhold_price(&self, symbol: &str, status: SymbolStatus) {
+        if let Err(e) = self.database.cache_symbol_status(symbol, &status).await {
+            error!("Failed to cache status for {}: {}", symbol, e);
+        }
+    }
+}
+
+type BatchResult = Result<Arc<HashMap<Pubkey, FeedAccount>>, Arc<String>>;
+
+// A batched read of feed accounts, awaited by every symbol that was due when it started
+#[derive(Clone)]
+struct FetchBatch {
+    started: Instant,
+    accounts: Shared<BoxFuture<'static, BatchResult>>,
+}
+
+// When a symbol polled at `now` is next due, given the time it was due at
+fn next_poll(due: Instant, now: Instant, interval: Duration, missed_ticks: MissedTicks) -> Instant {
+    match missed_ticks {
+        // Catch up one missed interval per round
+        MissedTicks::Burst => due + interval,
+        MissedTicks::Delay => now + interval,
+        // First time on the original cadence that is still ahead
+        MissedTicks::Skip => {
+            let behind = now.saturating_duration_since(due).as_nanos() / interval.as_nanos();
+            due + interval * (behind as u32 + 1)
+        }
+    }
+}
+
+// accountSubscribe notifies on every change, so while the subscription is live a cached account that
+// hasn't been notified is unchanged as of the latest slot seen on any feed. Stamping it with that slot
+// keeps quiet feeds from being dropped for slot lag.
+fn current_as_of(cache: &HashMap<Pubkey, FeedAccount>, slot: u64) -> HashMap<Pubkey, FeedAccount> {
+    cache
+        .iter()
+        .map(|(pubkey, feed)| (*pubkey, FeedAccount { account: feed.account.clone(), slot: feed.slot.max(slot) }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::price_aggregator::{AggregationStrategy, QuorumPolicy, SourcePrice, Thresholds};
+    use oracle_core::FixedPrice;
+    use solana_sdk::account::Account;
+
+    fn feed(slot: u64) -> FeedAccount {
+        FeedAccount { account: Account::default(), slot }
+    }
+
+    fn source(name: &str, slot: u64) -> SourcePrice {
+        SourcePrice {
+            price: FixedPrice::new(10_000, -2),
+            confidence: FixedPrice::new(10, -2),
+            timestamp: chrono::Utc::now().timestamp(),
+            slot,
+            source: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn quiet_subscribed_source_still_counts_toward_consensus() {
+        let pyth = Pubkey::new_unique();
+        let switchboard = Pubkey::new_unique();
+        // Pyth was just notified; Switchboard hasn't changed for 100 slots
+        let cache = HashMap::from([(pyth, feed(200)), (switchboard, feed(100))]);
+
+        let accounts = current_as_of(&cache, 200);
+        assert_eq!(accounts[&pyth].slot, 200);
+        assert_eq!(accounts[&switchboard].slot, 200);
+
+        let prices = vec![source("Pyth", accounts[&pyth].slot), source("Switchboard", accounts[&switchboard].slot)];
+        let consensus = PriceAggregator::new(10, 5)
+            .calculate_consensus(prices, &AggregationStrategy::Mean, &QuorumPolicy::default(), &Thresholds::default())
+            .unwrap();
+        assert_eq!(consensus.sources_used, 2);
+        assert!(consensus.rejected.is_empty());
+    }
+
+    #[test]
+    fn cached_slots_never_move_backwards() {
+        let pyth = Pubkey::new_unique();
+        let accounts = current_as_of(&HashMap::from([(pyth, feed(300))]), 200);
+        assert_eq!(accounts[&pyth].slot, 300);
+    }
+
+    #[test]
+    fn next_poll_follows_the_missed_ticks_policy() {
+        let due = Instant::now();
+        let interval = Duration::from_millis(500);
+
+        // On time: every policy moves one interval on
+        for missed_ticks in [MissedTicks::Burst, MissedTicks::Delay, MissedTicks::Skip] {
+            assert_eq!(next_poll(due, due, interval, missed_ticks), due + interval);
+        }
+
+        // 1.2s late
+        let now = due + Duration::from_millis(1_200);
+        assert_eq!(next_poll(due, now, interval, MissedTicks::Burst), due + interval);
+        assert_eq!(next_poll(due, now, interval, MissedTicks::Delay), now + interval);
+        assert_eq!(next_poll(due, now, interval, MissedTicks::Skip), due + Duration::from_millis(1_500));
+
+        // Exactly on a later tick: skip moves past it
+        let now = due + Duration::from_millis(1_000);
+        assert_eq!(next_poll(due, now, interval, MissedTicks::Skip), due + Duration::from_millis(1_500));
+    }
 }
@@ -0,0 +1,213 @@
+use solana_sdk::hash::hash;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+use oracle_core::consensus;
+use oracle_core::FixedPrice;
+use anyhow::{Result, anyhow, bail};
+
+// Borsh size of the contract's PriceData: i64 + u64 + i32 + i64 + enum tag
+const PRICE_DATA_LEN: usize = 29;
+
+// Mirrors the contract's PriceSource enum; the discriminant is the borsh tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    Pyth = 0,
+    Switchboard = 1,
+    Internal = 2,
+}
+
+// Mirrors the contract's PriceData, with price and confidence at the same exponent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceData {
+    pub price: i64,
+    pub confidence: u64,
+    pub expo: i32,
+    pub timestamp: i64,
+    pub source: PriceSource,
+}
+
+impl PriceData {
+    // Expresses price and confidence at their common exponent, as the contract expects
+    pub fn from_fixed(price: FixedPrice, confidence: FixedPrice, timestamp: i64, source: PriceSource) -> Result<Self> {
+        let expo = consensus::common_expo(&[price, confidence]);
+        let mantissa = consensus::mantissa_at(&price, expo)?;
+        let conf = consensus::mantissa_at(&confidence, expo)?;
+
+        Ok(Self {
+            price: i64::try_from(mantissa).map_err(|_| anyhow!("Price {} does not fit the contract's i64", price))?,
+            confidence: u64::try_from(conf).map_err(|_| anyhow!("Confidence {} does not fit the contract's u64", confidence))?,
+            expo,
+            timestamp,
+            source,
+        })
+    }
+
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.extend_from_slice(&self.price.to_le_bytes());
+        data.extend_from_slice(&self.confidence.to_le_bytes());
+        data.extend_from_slice(&self.expo.to_le_bytes());
+        data.extend_from_slice(&self.timestamp.to_le_bytes());
+        data.push(self.source as u8);
+    }
+
+    // Parses the return data of get_pyth_price / get_switchboard_price
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let data = return_data::<PRICE_DATA_LEN>(data)?;
+
+        let source = match data[28] {
+            0 => PriceSource::Pyth,
+            1 => PriceSource::Switchboard,
+            2 => PriceSource::Internal,
+            tag => bail!("Unknown PriceSource tag {}", tag),
+        };
+        Ok(Self {
+            price: i64::from_le_bytes(data[0..8].try_into()?),
+            confidence: u64::from_le_bytes(data[8..16].try_into()?),
+            expo: i32::from_le_bytes(data[16..20].try_into()?),
+            timestamp: i64::from_le_bytes(data[20..28].try_into()?),
+            source,
+        })
+    }
+}
+
+// The runtime strips trailing zero bytes from return data, so they are restored before decoding
+pub fn return_data<const N: usize>(data: &[u8]) -> Result<[u8; N]> {
+    if data.len() > N {
+        bail!("Return data is {} bytes, expected at most {}", data.len(), N);
+    }
+    let mut padded = [0u8; N];
+    padded[..data.len()].copy_from_slice(data);
+    Ok(padded)
+}
+
+// Anchor instruction discriminator: first 8 bytes of sha256("global:<name>")
+fn sighash(name: &str) -> Vec<u8> {
+    hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].to_vec()
+}
+
+// PDA the contract stores a config's published price in
+pub fn published_price_address(program_id: &Pubkey, oracle_config: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"price", oracle_config.as_ref()], program_id).0
+}
+
+pub fn publish_price(program_id: Pubkey, oracle_config: Pubkey, authority: Pubkey, price: &PriceData) -> Instruction {
+    let mut data = sighash("publish_price");
+    price.encode(&mut data);
+
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(oracle_config, false),
+            AccountMeta::new(published_price_address(&program_id, &oracle_config), false),
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub fn get_pyth_price(program_id: Pubkey, oracle_config: Pubkey, price_feed: Pubkey) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(oracle_config, false),
+            AccountMeta::new_readonly(price_feed, false),
+        ],
+        data: sighash("get_pyth_price"),
+    }
+}
+
+// Returns the median mantissa at the finest exponent among the prices
+pub fn validate_price_consensus(program_id: Pubkey, oracle_config: Pubkey, prices: &[PriceData]) -> Instruction {
+    let mut data = sighash("validate_price_consensus");
+    data.extend_from_slice(&(prices.len() as u32).to_le_bytes());
+    for price in prices {
+        price.encode(&mut data);
+    }
+
+    Instruction {
+        program_id,
+        accounts: vec![AccountMeta::new_readonly(oracle_config, false)],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_data(source: PriceSource) -> PriceData {
+        PriceData { price: -14_235_000, confidence: 5_000, expo: -5, timestamp: 1_700_000_000, source }
+    }
+
+    #[test]
+    fn price_data_round_trips_through_29_bytes() {
+        for source in [PriceSource::Pyth, PriceSource::Switchboard, PriceSource::Internal] {
+            let price = price_data(source);
+            let mut data = Vec::new();
+            price.encode(&mut data);
+
+            assert_eq!(data.len(), PRICE_DATA_LEN);
+            assert_eq!(data[28], source as u8);
+            assert_eq!(PriceData::decode(&data).unwrap(), price);
+        }
+    }
+
+    #[test]
+    fn decode_rejects_unknown_source_tags_and_oversized_data() {
+        let mut data = Vec::new();
+        price_data(PriceSource::Pyth).encode(&mut data);
+
+        data[28] = 3;
+        assert!(PriceData::decode(&data).is_err());
+
+        data[28] = 0;
+        data.push(0);
+        assert!(PriceData::decode(&data).is_err());
+    }
+
+    #[test]
+    fn return_data_restores_stripped_trailing_zeros() {
+        // A Pyth-tagged price with a zero timestamp ends in 9 zero bytes, which the runtime strips
+        let price = PriceData { timestamp: 0, ..price_data(PriceSource::Pyth) };
+        let mut data = Vec::new();
+        price.encode(&mut data);
+        let stripped = &data[..20];
+
+        assert_eq!(PriceData::decode(stripped).unwrap(), price);
+        assert_eq!(return_data::<8>(&[]).unwrap(), [0; 8]);
+        assert_eq!(return_data::<8>(&[1, 2]).unwrap(), [1, 2, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn sighash_matches_anchor_discriminators() {
+        assert_eq!(sighash("publish_price"), [117, 13, 6, 171, 29, 204, 11, 1]);
+        assert_eq!(sighash("get_pyth_price"), [183, 108, 128, 176, 75, 191, 37, 198]);
+        assert_eq!(sighash("validate_price_consensus"), [229, 228, 247, 217, 190, 52, 128, 22]);
+    }
+
+    #[test]
+    fn publish_price_lists_accounts_in_the_contracts_order() {
+        let program_id = Pubkey::new_unique();
+        let oracle_config = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let price = price_data(PriceSource::Internal);
+
+        let instruction = publish_price(program_id, oracle_config, authority, &price);
+
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(
+            instruction.accounts,
+            vec![
+                AccountMeta::new_readonly(oracle_config, false),
+                AccountMeta::new(published_price_address(&program_id, &oracle_config), false),
+                AccountMeta::new(authority, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ]
+        );
+        assert_eq!(instruction.data.len(), 8 + PRICE_DATA_LEN);
+        assert_eq!(instruction.data[..8], sighash("publish_price"));
+        assert_eq!(PriceData::decode(&instruction.data[8..]).unwrap(), price);
+    }
+}
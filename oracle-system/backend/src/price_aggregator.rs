@@ -1,83 +1,586 @@
 use serde::{Serialize, Deserialize};
 use anyhow::{Result, anyhow};
+use oracle_core::consensus;
+use oracle_core::FixedPrice;
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::disagreement_resolver::UnhealthyReport;
+
+// How the prices that survive the outlier filter are combined into one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AggregationStrategy {
+    Median,
+    #[default]
+    Mean,
+    // Inverse-confidence weighted mean
+    ConfidenceWeightedMean,
+    // Mean after dropping trim_pct percent of prices from each end
+    TrimmedMean { trim_pct: u8 },
+    // First listed source that is available, falling back down the list
+    SourcePriority { order: Vec<String> },
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourcePrice {
-    pub price: f64,
-    pub confidence: f64,
+    pub price: FixedPrice,
+    pub confidence: FixedPrice,
     pub timestamp: i64,
+    // Slot of the RPC context the source account was read at
+    pub slot: u64,
     pub source: String,
 }
 
+// A source that contributed to the consensus price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceContribution {
+    pub source: String,
+    pub price: FixedPrice,
+    pub confidence: FixedPrice,
+    pub age_secs: i64,
+    // Deviation from the median of the sources that passed the staleness checks
+    pub deviation_bps: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum RejectionReason {
+    Stale { age_secs: i64 },
+    // Timestamp further in the future than the allowed clock skew
+    FutureTimestamp { ahead_secs: i64 },
+    ConfidenceTooWide { confidence_bps: u64 },
+    SlotLag { slots_behind: u64 },
+    Deviation { deviation_bps: u64 },
+    // Scaled confidence interval does not overlap those of most other sources
+    IntervalDisjoint { deviation_bps: u64 },
+    // Lost a two-source disagreement to the named source
+    Disagreement { trusted: String },
+    FetchError { error: String },
+}
+
+impl RejectionReason {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RejectionReason::Stale { .. } => "stale",
+            RejectionReason::FutureTimestamp { .. } => "future_timestamp",
+            RejectionReason::ConfidenceTooWide { .. } => "confidence_too_wide",
+            RejectionReason::SlotLag { .. } => "slot_lag",
+            RejectionReason::Deviation { .. } => "deviation",
+            RejectionReason::IntervalDisjoint { .. } => "interval_disjoint",
+            RejectionReason::Disagreement { .. } => "disagreement",
+            RejectionReason::FetchError { .. } => "fetch_error",
+        }
+    }
+}
+
+// A source that was left out of the consensus price, with its price when one was read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedSource {
+    pub source: String,
+    pub price: Option<FixedPrice>,
+    #[serde(flatten)]
+    pub reason: RejectionReason,
+}
+
+// Minimum number of agreeing sources, and sources that must be among them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuorumPolicy {
+    pub min_sources: usize,
+    pub required_sources: Vec<String>,
+}
+
+impl Default for QuorumPolicy {
+    fn default() -> Self {
+        Self { min_sources: 1, required_sources: Vec::new() }
+    }
+}
+
+// Per-symbol limits, mirroring the on-chain OracleConfig: max_staleness in seconds,
+// max_confidence_bps and max_deviation_bps in basis points
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub max_staleness: i64,
+    pub max_confidence_bps: u64,
+    pub max_deviation_bps: u64,
+    // Per-source overrides, keyed by source name
+    pub sources: HashMap<String, SourceThresholds>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            max_staleness: 30,
+            max_confidence_bps: 200,
+            max_deviation_bps: 100,
+            sources: HashMap::new(),
+        }
+    }
+}
+
+// Unset fields fall back to the symbol's thresholds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceThresholds {
+    pub max_staleness: Option<i64>,
+    pub max_confidence_bps: Option<u64>,
+    pub max_deviation_bps: Option<u64>,
+}
+
+impl Thresholds {
+    pub fn max_staleness(&self, source: &str) -> i64 {
+        self.sources.get(source).and_then(|s| s.max_staleness).unwrap_or(self.max_staleness)
+    }
+
+    pub fn max_confidence_bps(&self, source: &str) -> u64 {
+        self.sources.get(source).and_then(|s| s.max_confidence_bps).unwrap_or(self.max_confidence_bps)
+    }
+
+    pub fn max_deviation_bps(&self, source: &str) -> u64 {
+        self.sources.get(source).and_then(|s| s.max_deviation_bps).unwrap_or(self.max_deviation_bps)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumFailure {
+    pub sources_used: usize,
+    pub min_sources: usize,
+    pub missing_required: Vec<String>,
+    pub rejected: Vec<RejectedSource>,
+    pub timestamp: i64,
+}
+
+// Why a symbol currently has no published price; cached in place of the price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SymbolStatus {
+    InsufficientQuorum(QuorumFailure),
+    Unhealthy(UnhealthyReport),
+}
+
+#[derive(Debug, Error)]
+pub enum AggregationError {
+    #[error("Insufficient quorum: {} of {} sources agree, missing required {:?}", .0.sources_used, .0.min_sources, .0.missing_required)]
+    InsufficientQuorum(QuorumFailure),
+    // Exactly two sources, both outside the threshold around their midpoint
+    #[error("Sources disagree beyond the deviation threshold")]
+    Disagreement(Vec<SourcePrice>),
+    #[error("Symbol unhealthy: {}", .0.reason)]
+    Unhealthy(UnhealthyReport),
+    #[error(transparent)]
+    Consensus(#[from] oracle_core::ConsensusError),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsensusPrice {
-    pub price: f64,
-    pub confidence: f64,
+    pub price: FixedPrice,
+    pub confidence: FixedPrice,
     pub timestamp: i64,
     pub sources_used: usize,
+    pub strategy: AggregationStrategy,
+    pub sources: Vec<SourceContribution>,
+    pub rejected: Vec<RejectedSource>,
 }
 
 pub struct PriceAggregator {
-    max_deviation_bps: u64,
+    max_slot_lag: u64,
+    // How far ahead of local time a source timestamp may be before it is treated as bogus
+    max_clock_skew_secs: i64,
+    // When set, sources agree if their confidence intervals scaled by this many sigma overlap,
+    // instead of by the bps threshold around the median
+    overlap_sigma: Option<f64>,
 }
 
 impl PriceAggregator {
-    pub fn new(max_deviation_bps: u64) -> Self {
-        Self { max_deviation_bps }
+    pub fn new(max_slot_lag: u64, max_clock_skew_secs: i64) -> Self {
+        Self { max_slot_lag, max_clock_skew_secs, overlap_sigma: None }
     }
 
-    pub fn calculate_consensus(&self, mut prices: Vec<SourcePrice>) -> Result<ConsensusPrice> {
-        if prices.is_empty() {
-            return Err(anyhow!("No prices to aggregate"));
-        }
+    pub fn set_overlap_sigma(&mut self, sigma: f64) {
+        self.overlap_sigma = Some(sigma);
+    }
+
+    pub fn calculate_consensus(
+        &self,
+        mut prices: Vec<SourcePrice>,
+        strategy: &AggregationStrategy,
+        quorum: &QuorumPolicy,
+        thresholds: &Thresholds,
+    ) -> Result<ConsensusPrice, AggregationError> {
+        let mut rejected = Vec::new();
 
-        // Filter out stale prices (e.g., > 30 seconds old)
+        // Drop future-dated, stale and overly uncertain sources against their own thresholds
         let now = chrono::Utc::now().timestamp();
-        prices.retain(|p| now - p.timestamp < 30);
+        for p in std::mem::take(&mut prices) {
+            let age_secs = now - p.timestamp;
+            let confidence_bps = consensus::confidence_bps(&p.price, &p.confidence)
+                .map(|bps| u64::try_from(bps).unwrap_or(u64::MAX))
+                .unwrap_or(u64::MAX);
+
+            let reason = if -age_secs > self.max_clock_skew_secs {
+                Some(RejectionReason::FutureTimestamp { ahead_secs: -age_secs })
+            } else if age_secs > thresholds.max_staleness(&p.source) {
+                Some(RejectionReason::Stale { age_secs })
+            } else if confidence_bps > thresholds.max_confidence_bps(&p.source) {
+                Some(RejectionReason::ConfidenceTooWide { confidence_bps })
+            } else {
+                None
+            };
+
+            match reason {
+                Some(reason) => rejected.push(RejectedSource { source: p.source, price: Some(p.price), reason }),
+                None => prices.push(p),
+            }
+        }
 
         if prices.is_empty() {
-            return Err(anyhow!("All prices are stale"));
+            return Err(Self::insufficient_quorum(&prices, quorum, rejected, now));
         }
 
-        // Sort by price
-        prices.sort_by(|a, b| a.price.partial_cmp(&b.price).unwrap());
+        // Sources read at an older slot than the freshest one may reflect a different market state
+        let freshest_slot = prices.iter().map(|p| p.slot).max().unwrap_or(0);
+        let (current, lagging): (Vec<_>, Vec<_>) = prices
+            .into_iter()
+            .partition(|p| freshest_slot - p.slot <= self.max_slot_lag);
+        rejected.extend(lagging.into_iter().map(|p| RejectedSource {
+            reason: RejectionReason::SlotLag { slots_behind: freshest_slot - p.slot },
+            price: Some(p.price),
+            source: p.source,
+        }));
+        prices = current;
 
-        // Median calculation
-        let mid = prices.len() / 2;
-        let median_price = if prices.len() % 2 == 0 {
-            (prices[mid - 1].price + prices[mid].price) / 2.0
-        } else {
-            prices[mid].price
+        // Median, outlier filter and aggregation come from oracle-core so they match the contract's arithmetic
+        let source_prices: Vec<FixedPrice> = prices.iter().map(|p| p.price).collect();
+        let agreement = consensus::agreement(&source_prices, thresholds.max_deviation_bps)?;
+        let within = match self.overlap_sigma {
+            Some(sigma) => {
+                let intervals: Vec<_> = prices.iter().map(|p| (p.price, p.confidence)).collect();
+                let sigma_milli = (sigma * consensus::SIGMA_SCALE as f64).round() as u64;
+                consensus::interval_overlap(&intervals, sigma_milli)?
+            }
+            None => prices
+                .iter()
+                .zip(&agreement.deviation_bps)
+                .map(|(p, deviation_bps)| *deviation_bps <= thresholds.max_deviation_bps(&p.source) as u128)
+                .collect(),
         };
 
-        // Check deviation
-        let mut valid_prices = Vec::new();
-        for p in prices {
-            let deviation = (p.price - median_price).abs();
-            let deviation_bps = (deviation / median_price * 10000.0) as u64;
+        // With two sources the median is their midpoint, so the outlier can't be identified here
+        let candidates = (prices.len() == 2).then(|| prices.clone());
 
-            if deviation_bps <= self.max_deviation_bps {
+        let mut valid_prices = Vec::new();
+        let mut sources = Vec::new();
+        for ((p, within), deviation_bps) in prices.into_iter().zip(within).zip(agreement.deviation_bps) {
+            let deviation_bps = u64::try_from(deviation_bps).unwrap_or(u64::MAX);
+            if within {
+                sources.push(SourceContribution {
+                    source: p.source.clone(),
+                    price: p.price,
+                    confidence: p.confidence,
+                    age_secs: now - p.timestamp,
+                    deviation_bps,
+                });
                 valid_prices.push(p);
+            } else {
+                let reason = match self.overlap_sigma {
+                    Some(_) => RejectionReason::IntervalDisjoint { deviation_bps },
+                    None => RejectionReason::Deviation { deviation_bps },
+                };
+                rejected.push(RejectedSource {
+                    source: p.source,
+                    price: Some(p.price),
+                    reason,
+                });
             }
         }
 
         if valid_prices.is_empty() {
-            return Err(anyhow!("No prices within deviation threshold"));
+            if let Some(candidates) = candidates {
+                return Err(AggregationError::Disagreement(candidates));
+            }
         }
 
-        // Recalculate median of valid prices or use weighted average based on confidence
-        // For simplicity, using average of valid prices here
-        let sum_price: f64 = valid_prices.iter().map(|p| p.price).sum();
-        let avg_price = sum_price / valid_prices.len() as f64;
+        // Refuse to publish a degraded price from too few or the wrong sources, and never from none
+        let quorum_met = valid_prices.len() >= quorum.min_sources.max(1)
+            && quorum.required_sources.iter().all(|required| valid_prices.iter().any(|p| &p.source == required));
+        if !quorum_met {
+            return Err(Self::insufficient_quorum(&valid_prices, quorum, rejected, now));
+        }
 
-        // Conservative confidence: max confidence interval of used sources
-        let max_conf = valid_prices.iter().map(|p| p.confidence).fold(0.0, f64::max);
+        let price = Self::aggregate(&valid_prices, strategy)?;
+        let confidence = Self::combined_confidence(&valid_prices, strategy, price)?;
 
         Ok(ConsensusPrice {
-            price: avg_price,
-            confidence: max_conf,
+            price,
+            confidence,
             timestamp: now,
             sources_used: valid_prices.len(),
+            strategy: strategy.clone(),
+            sources,
+            rejected,
+        })
+    }
+
+    // Also covers rounds where every source was rejected, so the symbol is withheld rather than left to expire
+    fn insufficient_quorum(used: &[SourcePrice], quorum: &QuorumPolicy, rejected: Vec<RejectedSource>, now: i64) -> AggregationError {
+        let missing_required = quorum
+            .required_sources
+            .iter()
+            .filter(|required| !used.iter().any(|p| &p.source == *required))
+            .cloned()
+            .collect();
+        AggregationError::InsufficientQuorum(QuorumFailure {
+            sources_used: used.len(),
+            min_sources: quorum.min_sources,
+            missing_required,
+            rejected,
+            timestamp: now,
         })
     }
+
+    fn aggregate(prices: &[SourcePrice], strategy: &AggregationStrategy) -> Result<FixedPrice> {
+        let values: Vec<FixedPrice> = prices.iter().map(|p| p.price).collect();
+
+        let price = match strategy {
+            AggregationStrategy::Median => consensus::median_price(&values)?,
+            AggregationStrategy::Mean => consensus::mean(&values)?,
+            AggregationStrategy::ConfidenceWeightedMean => {
+                let pairs: Vec<_> = prices.iter().map(|p| (p.price, p.confidence)).collect();
+                consensus::confidence_weighted_mean(&pairs)?
+            }
+            AggregationStrategy::TrimmedMean { trim_pct } => consensus::trimmed_mean(&values, *trim_pct)?,
+            AggregationStrategy::SourcePriority { order } => order
+                .iter()
+                .find_map(|source| prices.iter().find(|p| &p.source == source))
+                .map(|p| p.price)
+                .ok_or_else(|| anyhow!("None of the prioritised sources {:?} are available", order))?,
+        };
+
+        Ok(price)
+    }
+
+    // One-sigma uncertainty of the aggregate: source confidences propagated through the strategy's
+    // weights as independent errors, plus the weighted spread of the sources around the aggregate so
+    // that sources which disagree within the threshold still widen the interval
+    fn combined_confidence(prices: &[SourcePrice], strategy: &AggregationStrategy, aggregate: FixedPrice) -> Result<FixedPrice> {
+        let weights: Vec<f64> = match strategy {
+            AggregationStrategy::ConfidenceWeightedMean => prices
+                .iter()
+                .map(|p| 1.0 / p.confidence.to_f64().abs().max(f64::MIN_POSITIVE))
+                .collect(),
+            AggregationStrategy::SourcePriority { .. } => {
+                return prices
+                    .iter()
+                    .find(|p| p.price == aggregate)
+                    .map(|p| p.confidence)
+                    .ok_or_else(|| anyhow!("Prioritised source missing from the used prices"));
+            }
+            _ => vec![1.0; prices.len()],
+        };
+
+        let total_weight: f64 = weights.iter().sum();
+        let aggregate = aggregate.to_f64();
+        let mut propagated = 0.0;
+        let mut spread = 0.0;
+        for (p, weight) in prices.iter().zip(&weights) {
+            let share = weight / total_weight;
+            propagated += (share * p.confidence.to_f64()).powi(2);
+            spread += share * (p.price.to_f64() - aggregate).powi(2);
+        }
+
+        // Report at the finest exponent the sources used for their confidences
+        let confidences: Vec<FixedPrice> = prices.iter().map(|p| p.confidence).collect();
+        let expo = consensus::common_expo(&confidences);
+        let mantissa = ((propagated + spread).sqrt() * 10f64.powi(-expo)).round();
+        Ok(FixedPrice::new(mantissa as i64, expo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(source: &str, price: i64, age_secs: i64) -> SourcePrice {
+        SourcePrice {
+            price: FixedPrice::new(price, -2),
+            confidence: FixedPrice::new(price / 1_000, -2),
+            timestamp: chrono::Utc::now().timestamp() - age_secs,
+            slot: 100,
+            source: source.to_string(),
+        }
+    }
+
+    fn priority(order: &[&str]) -> AggregationStrategy {
+        AggregationStrategy::SourcePriority { order: order.iter().map(|s| s.to_string()).collect() }
+    }
+
+    #[test]
+    fn source_priority_falls_back_when_the_preferred_source_is_rejected() {
+        let aggregator = PriceAggregator::new(10, 5);
+        let prices = vec![source("Pyth", 10_000, 0), source("Switchboard", 10_010, 120)];
+
+        let consensus = aggregator
+            .calculate_consensus(prices, &priority(&["Switchboard", "Pyth"]), &QuorumPolicy::default(), &Thresholds::default())
+            .unwrap();
+
+        assert_eq!(consensus.price, FixedPrice::new(10_000, -2));
+        assert_eq!(consensus.confidence, FixedPrice::new(10, -2));
+        assert_eq!(consensus.sources_used, 1);
+        assert_eq!(consensus.rejected.len(), 1);
+        assert_eq!(consensus.rejected[0].source, "Switchboard");
+        assert_eq!(consensus.rejected[0].reason.kind(), "stale");
+    }
+
+    #[test]
+    fn source_priority_uses_the_first_listed_source_when_all_agree() {
+        let aggregator = PriceAggregator::new(10, 5);
+        let prices = vec![source("Pyth", 10_000, 0), source("Switchboard", 10_010, 0)];
+
+        let consensus = aggregator
+            .calculate_consensus(prices, &priority(&["Switchboard", "Pyth"]), &QuorumPolicy::default(), &Thresholds::default())
+            .unwrap();
+
+        assert_eq!(consensus.price, FixedPrice::new(10_010, -2));
+        assert_eq!(consensus.sources_used, 2);
+    }
+
+    #[test]
+    fn source_priority_fails_when_no_listed_source_is_available() {
+        let aggregator = PriceAggregator::new(10, 5);
+        let prices = vec![source("Pyth", 10_000, 0)];
+
+        let result = aggregator.calculate_consensus(prices, &priority(&["Switchboard"]), &QuorumPolicy::default(), &Thresholds::default());
+
+        assert!(matches!(result, Err(AggregationError::Other(_))));
+    }
+
+    fn quorum_failure(result: Result<ConsensusPrice, AggregationError>) -> QuorumFailure {
+        match result {
+            Err(AggregationError::InsufficientQuorum(failure)) => failure,
+            other => panic!("expected insufficient quorum, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn every_source_stale_is_insufficient_quorum() {
+        let aggregator = PriceAggregator::new(10, 5);
+        let prices = vec![source("Pyth", 10_000, 120), source("Switchboard", 10_010, 120)];
+        let quorum = QuorumPolicy { min_sources: 1, required_sources: vec!["Pyth".to_string()] };
+
+        let failure = quorum_failure(aggregator.calculate_consensus(prices, &AggregationStrategy::Mean, &quorum, &Thresholds::default()));
+
+        assert_eq!(failure.sources_used, 0);
+        assert_eq!(failure.missing_required, vec!["Pyth".to_string()]);
+        assert_eq!(failure.rejected.len(), 2);
+        assert!(failure.rejected.iter().all(|rejection| rejection.reason.kind() == "stale"));
+    }
+
+    #[test]
+    fn no_prices_is_insufficient_quorum() {
+        let aggregator = PriceAggregator::new(10, 5);
+
+        let failure = quorum_failure(aggregator.calculate_consensus(Vec::new(), &AggregationStrategy::Mean, &QuorumPolicy::default(), &Thresholds::default()));
+
+        assert_eq!(failure.sources_used, 0);
+        assert!(failure.rejected.is_empty());
+    }
+
+    #[test]
+    fn no_overlapping_intervals_is_insufficient_quorum() {
+        let mut aggregator = PriceAggregator::new(10, 5);
+        aggregator.set_overlap_sigma(1.0);
+        let mut prices = vec![source("Pyth", 10_000, 0), source("Switchboard", 11_000, 0), source("Internal", 12_000, 0)];
+        prices.iter_mut().for_each(|p| p.confidence = FixedPrice::new(1, -2));
+        let thresholds = Thresholds { max_deviation_bps: 10_000, ..Thresholds::default() };
+
+        let failure = quorum_failure(aggregator.calculate_consensus(prices, &AggregationStrategy::Mean, &QuorumPolicy::default(), &thresholds));
+
+        assert_eq!(failure.sources_used, 0);
+        assert_eq!(failure.rejected.len(), 3);
+        assert!(failure.rejected.iter().all(|rejection| rejection.reason.kind() == "interval_disjoint"));
+    }
+
+    #[test]
+    fn source_exactly_at_the_slot_lag_limit_is_kept() {
+        let aggregator = PriceAggregator::new(10, 5);
+        let mut lagging = source("Switchboard", 10_010, 0);
+        lagging.slot = 90;
+
+        let consensus = aggregator
+            .calculate_consensus(vec![source("Pyth", 10_000, 0), lagging], &AggregationStrategy::Mean, &QuorumPolicy::default(), &Thresholds::default())
+            .unwrap();
+
+        assert_eq!(consensus.sources_used, 2);
+        assert!(consensus.rejected.is_empty());
+    }
+
+    #[test]
+    fn source_past_the_slot_lag_limit_is_rejected() {
+        let aggregator = PriceAggregator::new(10, 5);
+        let mut lagging = source("Switchboard", 10_010, 0);
+        lagging.slot = 89;
+
+        let consensus = aggregator
+            .calculate_consensus(vec![source("Pyth", 10_000, 0), lagging], &AggregationStrategy::Mean, &QuorumPolicy::default(), &Thresholds::default())
+            .unwrap();
+
+        assert_eq!(consensus.sources_used, 1);
+        assert_eq!(consensus.rejected.len(), 1);
+        assert_eq!(consensus.rejected[0].source, "Switchboard");
+        assert!(matches!(consensus.rejected[0].reason, RejectionReason::SlotLag { slots_behind: 11 }));
+    }
+
+    fn overrides(source: &str, overrides: SourceThresholds) -> Thresholds {
+        Thresholds { sources: HashMap::from([(source.to_string(), overrides)]), ..Thresholds::default() }
+    }
+
+    #[test]
+    fn per_source_staleness_override_applies_only_to_that_source() {
+        let aggregator = PriceAggregator::new(10, 5);
+        let thresholds = overrides("Switchboard", SourceThresholds { max_staleness: Some(120), ..SourceThresholds::default() });
+
+        let consensus = aggregator
+            .calculate_consensus(vec![source("Pyth", 10_000, 60), source("Switchboard", 10_010, 60)], &AggregationStrategy::Mean, &QuorumPolicy::default(), &thresholds)
+            .unwrap();
+
+        assert_eq!(consensus.sources_used, 1);
+        assert_eq!(consensus.rejected.len(), 1);
+        assert_eq!(consensus.rejected[0].source, "Pyth");
+        assert!(matches!(consensus.rejected[0].reason, RejectionReason::Stale { age_secs } if age_secs >= 60));
+    }
+
+    #[test]
+    fn per_source_confidence_override_applies_only_to_that_source() {
+        let aggregator = PriceAggregator::new(10, 5);
+        // Test sources carry a 10 bps confidence interval
+        let thresholds = overrides("Pyth", SourceThresholds { max_confidence_bps: Some(5), ..SourceThresholds::default() });
+
+        let consensus = aggregator
+            .calculate_consensus(vec![source("Pyth", 10_000, 0), source("Switchboard", 10_010, 0)], &AggregationStrategy::Mean, &QuorumPolicy::default(), &thresholds)
+            .unwrap();
+
+        assert_eq!(consensus.sources_used, 1);
+        assert_eq!(consensus.rejected.len(), 1);
+        assert_eq!(consensus.rejected[0].source, "Pyth");
+        assert!(matches!(consensus.rejected[0].reason, RejectionReason::ConfidenceTooWide { confidence_bps: 10 }));
+    }
+
+    #[test]
+    fn timestamps_past_the_clock_skew_are_rejected() {
+        let aggregator = PriceAggregator::new(10, 5);
+
+        // Negative age puts the timestamp in the future; the first source also
+        // allows a second for the clock ticking between setup and filtering
+        let consensus = aggregator
+            .calculate_consensus(vec![source("Pyth", 10_000, -4), source("Switchboard", 10_010, -30)], &AggregationStrategy::Mean, &QuorumPolicy::default(), &Thresholds::default())
+            .unwrap();
+
+        assert_eq!(consensus.sources_used, 1);
+        assert_eq!(consensus.rejected.len(), 1);
+        assert_eq!(consensus.rejected[0].source, "Switchboard");
+        assert!(matches!(consensus.rejected[0].reason, RejectionReason::FutureTimestamp { ahead_secs } if ahead_secs > 5));
+    }
 }
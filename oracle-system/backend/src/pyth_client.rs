@@ -1,53 +1,200 @@
-use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
 use solana_sdk::pubkey::Pubkey;
-use pyth_sdk_solana::load_price_feed_from_account;
-use anyhow::{Result, anyhow};
+use pyth_sdk_solana::state::{load_price_account, PriceStatus};
+use pyth_sdk_solana::PythError;
+use crate::hermes_client::HermesClient;
+use crate::pyth_accumulator::FeedId;
+use oracle_core::FixedPrice;
+use serde::Serialize;
+use thiserror::Error;
 use std::sync::Arc;
 
+#[derive(Debug, Error)]
+pub enum PythClientError {
+    #[error("Failed to load Pyth feed {feed}: {source:?}")]
+    InvalidAccount { feed: Pubkey, source: PythError },
+    #[error("Pyth feed {feed} is not trading (status {status:?})")]
+    NotTrading { feed: Pubkey, status: PriceStatus },
+    #[error("No Hermes update received yet for Pyth feed {0}")]
+    NoHermesUpdate(String),
+}
+
+// Aggregate, previous and EMA values of a Pyth price account at the feed exponent
+#[derive(Debug, Clone, Serialize)]
+pub struct PythPriceData {
+    pub price: FixedPrice,
+    pub confidence: FixedPrice,
+    pub publish_time: i64,
+    pub status: PriceStatus,
+    // Publisher count and previous aggregate are only available from on-chain accounts
+    pub num_publishers: Option<u32>,
+    pub prev_price: Option<FixedPrice>,
+    pub prev_confidence: Option<FixedPrice>,
+    pub prev_publish_time: i64,
+    pub ema_price: FixedPrice,
+    pub ema_confidence: FixedPrice,
+}
+
+// Decodes Pyth price accounts fetched by the AccountFetcher, or reads verified Hermes updates when
+// constructed with a HermesClient. Staleness is left to the aggregator, which applies each symbol's
+// per-source max_staleness.
+#[derive(Default)]
 pub struct PythClient {
-    rpc_client: Arc<RpcClient>,
+    hermes: Option<Arc<HermesClient>>,
 }
 
 impl PythClient {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
-        Self {
-            rpc_client,
+    pub fn new() -> Self {
+        Self { hermes: None }
+    }
+
+    pub fn with_hermes(hermes: Arc<HermesClient>) -> Self {
+        Self { hermes: Some(hermes) }
+    }
+
+    pub fn hermes(&self) -> Option<Arc<HermesClient>> {
+        self.hermes.clone()
+    }
+
+    // Decodes the account without the trading status check
+    pub fn get_price_data_unchecked(&self, price_feed_id: &Pubkey, account: &Account) -> Result<PythPriceData, PythClientError> {
+        let price_account = load_price_account(&account.data)
+            .map_err(|source| PythClientError::InvalidAccount { feed: *price_feed_id, source })?;
+
+        let expo = price_account.expo;
+
+        Ok(PythPriceData {
+            price: FixedPrice::new(price_account.agg.price, expo),
+            confidence: FixedPrice::new(price_account.agg.conf as i64, expo),
+            publish_time: price_account.timestamp,
+            status: price_account.agg.status,
+            num_publishers: Some(price_account.num_qt),
+            prev_price: Some(FixedPrice::new(price_account.prev_price, expo)),
+            prev_confidence: Some(FixedPrice::new(price_account.prev_conf as i64, expo)),
+            prev_publish_time: price_account.prev_timestamp,
+            ema_price: FixedPrice::new(price_account.ema_price.val, expo),
+            ema_confidence: FixedPrice::new(price_account.ema_conf.val, expo),
+        })
+    }
+
+    // Rejects halted, auctioning or unknown feeds
+    pub fn get_price_data(&self, price_feed_id: &Pubkey, account: &Account) -> Result<PythPriceData, PythClientError> {
+        let data = self.get_price_data_unchecked(price_feed_id, account)?;
+
+        if data.status != PriceStatus::Trading {
+            return Err(PythClientError::NotTrading { feed: *price_feed_id, status: data.status });
         }
+
+        Ok(data)
     }
 
-    pub async fn get_price(&self, price_feed_id: &Pubkey) -> Result<f64> {
-        let client = self.rpc_client.clone();
-        let feed_id = *price_feed_id;
+    // Hermes only publishes aggregates with Trading status, so no status check is needed
+    pub fn get_hermes_price_data(&self, feed_id: &FeedId) -> Result<PythPriceData, PythClientError> {
+        let message = self
+            .hermes
+            .as_ref()
+            .and_then(|hermes| hermes.latest_price(feed_id))
+            .ok_or_else(|| PythClientError::NoHermesUpdate(hex::encode(feed_id)))?;
+
+        let expo = message.expo;
+        let data = PythPriceData {
+            price: FixedPrice::new(message.price, expo),
+            confidence: FixedPrice::new(message.conf as i64, expo),
+            publish_time: message.publish_time,
+            status: PriceStatus::Trading,
+            num_publishers: None,
+            prev_price: None,
+            prev_confidence: None,
+            prev_publish_time: message.prev_publish_time,
+            ema_price: FixedPrice::new(message.ema_price, expo),
+            ema_confidence: FixedPrice::new(message.ema_conf as i64, expo),
+        };
 
-        let mut account = tokio::task::spawn_blocking(move || {
-            client.get_account(&feed_id)
-        }).await??;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyth_sdk_solana::state::{AccountType, PriceAccount, MAGIC, VERSION_2};
+
+    fn price_account(status: PriceStatus) -> PriceAccount {
+        let mut price = PriceAccount {
+            magic: MAGIC,
+            ver: VERSION_2,
+            atype: AccountType::Price as u32,
+            expo: -8,
+            num_qt: 7,
+            timestamp: 1_700_000_000,
+            prev_price: 6_499_000_000_000,
+            prev_conf: 2_000_000_000,
+            prev_timestamp: 1_699_999_999,
+            ..PriceAccount::default()
+        };
+        price.agg.price = 6_500_000_000_000;
+        price.agg.conf = 3_000_000_000;
+        price.agg.status = status;
+        price.ema_price.val = 6_490_000_000_000;
+        price.ema_conf.val = 2_500_000_000;
+        price
+    }
 
-        let price_feed = load_price_feed_from_account(&feed_id, &mut account)
-            .map_err(|e| anyhow!("Failed to load Pyth feed: {:?}", e))?;
+    fn account(price: &PriceAccount) -> Account {
+        Account { data: bytemuck::bytes_of(price).to_vec(), ..Account::default() }
+    }
 
-        let current_price = price_feed.get_price_unchecked();
+    #[test]
+    fn decodes_a_trading_price_account() {
+        let data = PythClient::new().get_price_data(&Pubkey::new_unique(), &account(&price_account(PriceStatus::Trading))).unwrap();
 
-        let price = current_price.price as f64 * 10f64.powi(current_price.expo);
-        Ok(price)
+        assert_eq!(data.price, FixedPrice::new(6_500_000_000_000, -8));
+        assert_eq!(data.confidence, FixedPrice::new(3_000_000_000, -8));
+        assert_eq!(data.publish_time, 1_700_000_000);
+        assert_eq!(data.num_publishers, Some(7));
+        assert_eq!(data.prev_price, Some(FixedPrice::new(6_499_000_000_000, -8)));
+        assert_eq!(data.prev_publish_time, 1_699_999_999);
+        assert_eq!(data.ema_price, FixedPrice::new(6_490_000_000_000, -8));
+        assert_eq!(data.ema_confidence, FixedPrice::new(2_500_000_000, -8));
     }
 
-    pub async fn get_price_data(&self, price_feed_id: &Pubkey) -> Result<(f64, f64, i64)> {
-        let client = self.rpc_client.clone();
-        let feed_id = *price_feed_id;
+    #[test]
+    fn rejects_feeds_that_are_not_trading() {
+        let feed = Pubkey::new_unique();
+        let client = PythClient::new();
+
+        for status in [PriceStatus::Unknown, PriceStatus::Halted, PriceStatus::Auction] {
+            let account = account(&price_account(status));
+            let error = client.get_price_data(&feed, &account).unwrap_err();
+            assert!(matches!(error, PythClientError::NotTrading { feed: f, status: s } if f == feed && s == status));
 
-        let mut account = tokio::task::spawn_blocking(move || {
-            client.get_account(&feed_id)
-        }).await??;
+            // The unchecked path still decodes it
+            assert_eq!(client.get_price_data_unchecked(&feed, &account).unwrap().status, status);
+        }
+    }
 
-        let price_feed = load_price_feed_from_account(&feed_id, &mut account)
-            .map_err(|e| anyhow!("Failed to load Pyth feed: {:?}", e))?;
+    #[test]
+    fn rejects_malformed_accounts() {
+        let feed = Pubkey::new_unique();
+        let client = PythClient::new();
+        let valid = price_account(PriceStatus::Trading);
 
-        let current_price = price_feed.get_price_unchecked();
+        let cases = [
+            (Account { data: vec![0; 16], ..Account::default() }, PythError::InvalidAccountData),
+            (account(&PriceAccount { magic: 0, ..valid }), PythError::InvalidAccountData),
+            (account(&PriceAccount { ver: 1, ..valid }), PythError::BadVersionNumber),
+            (account(&PriceAccount { atype: AccountType::Product as u32, ..valid }), PythError::WrongAccountType),
+        ];
+
+        for (account, expected) in cases {
+            let error = client.get_price_data(&feed, &account).unwrap_err();
+            assert!(matches!(error, PythClientError::InvalidAccount { feed: f, ref source } if f == feed && *source == expected), "{error:?}");
+        }
+    }
 
-        let price = current_price.price as f64 * 10f64.powi(current_price.expo);
-        let conf = current_price.conf as f64 * 10f64.powi(current_price.expo);
-        
-        Ok((price, conf, current_price.publish_time))
+    #[test]
+    fn hermes_reads_fail_without_an_update() {
+        let error = PythClient::new().get_hermes_price_data(&[7; 32]).unwrap_err();
+        assert!(matches!(error, PythClientError::NoHermesUpdate(ref feed) if *feed == hex::encode([7; 32])));
     }
 }
@@ -0,0 +1,243 @@
+use crate::alerting::AlertEngine;
+use crate::database::Database;
+use crate::metrics::Metrics;
+use crate::oracle_manager::{OracleConfig, OracleManager};
+use crate::oracle_program::{self, PriceData, PriceSource};
+use crate::price_aggregator::ConsensusPrice;
+use crate::rpc_pool::RpcPool;
+use base64::Engine;
+use oracle_core::consensus;
+use oracle_core::FixedPrice;
+use serde::{Serialize, Deserialize};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use log::{info, warn, error};
+
+// One check of a symbol's cached consensus price against the oracle program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reconciliation {
+    pub symbol: String,
+    pub backend_price: FixedPrice,
+    // get_pyth_price simulated against the symbol's Pyth account
+    pub onchain_pyth_price: Option<FixedPrice>,
+    pub pyth_divergence_bps: Option<u64>,
+    pub pyth_error: Option<String>,
+    // validate_price_consensus simulated with the source prices the backend aggregated
+    pub onchain_consensus_price: Option<FixedPrice>,
+    pub consensus_divergence_bps: Option<u64>,
+    pub consensus_error: Option<String>,
+    pub timestamp: i64,
+}
+
+impl Reconciliation {
+    // The contract rejected the backend's sources, or returned a price too far from the backend's
+    pub fn diverged(&self, alert_bps: u64) -> bool {
+        self.consensus_error.is_some()
+            || self.pyth_divergence_bps.is_some_and(|bps| bps > alert_bps)
+            || self.consensus_divergence_bps.is_some_and(|bps| bps > alert_bps)
+    }
+
+    // One line per failed check, for the alert summary
+    pub fn describe(&self, alert_bps: u64) -> String {
+        let mut checks = Vec::new();
+        if let Some(error) = &self.consensus_error {
+            checks.push(format!("validate_price_consensus rejected the sources ({})", error));
+        }
+        if let Some(bps) = self.consensus_divergence_bps.filter(|bps| *bps > alert_bps) {
+            checks.push(format!("consensus {} bps from the backend", bps));
+        }
+        if let Some(bps) = self.pyth_divergence_bps.filter(|bps| *bps > alert_bps) {
+            checks.push(format!("Pyth {} bps from the backend", bps));
+        }
+        format!("{} (limit {} bps)", checks.join(", "), alert_bps)
+    }
+}
+
+// Periodically checks that the oracle program agrees with the backend's cached prices
+pub struct ReconciliationMonitor {
+    rpc_pool: Arc<RpcPool>,
+    database: Arc<Database>,
+    manager: Arc<OracleManager>,
+    metrics: Arc<Metrics>,
+    alerts: Option<Arc<AlertEngine>>,
+    program_id: Pubkey,
+    // Simulation skips signature checks, but the fee payer must still be an existing account
+    fee_payer: Pubkey,
+    alert_bps: u64,
+}
+
+impl ReconciliationMonitor {
+    pub fn new(
+        rpc_pool: Arc<RpcPool>,
+        database: Arc<Database>,
+        manager: Arc<OracleManager>,
+        metrics: Arc<Metrics>,
+        program_id: Pubkey,
+        fee_payer: Pubkey,
+        alert_bps: u64,
+    ) -> Self {
+        Self {
+            rpc_pool,
+            database,
+            manager,
+            metrics,
+            alerts: None,
+            program_id,
+            fee_payer,
+            alert_bps,
+        }
+    }
+
+    pub fn set_alerts(&mut self, alerts: Arc<AlertEngine>) {
+        self.alerts = Some(alerts);
+    }
+
+    pub async fn run(self: Arc<Self>, interval: Duration, mut shutdown: watch::Receiver<bool>) {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => return,
+            }
+
+            // History is written by the leader only, like price history
+            if !self.manager.is_leader() {
+                continue;
+            }
+
+            for config in self.manager.configs() {
+                if config.oracle_account.is_none() {
+                    continue;
+                }
+                if let Err(e) = self.reconcile(&config).await {
+                    error!("Reconciliation failed for {}: {}", config.symbol, e);
+                }
+            }
+        }
+    }
+
+    async fn reconcile(&self, config: &OracleConfig) -> Result<()> {
+        let Some(oracle_account) = config.oracle_account else {
+            return Ok(());
+        };
+        let Some(price) = self.database.get_cached_price(&config.symbol).await? else {
+            return Ok(());
+        };
+
+        let pyth = self
+            .simulate(oracle_program::get_pyth_price(self.program_id, oracle_account, config.pyth_feed))
+            .await?
+            .and_then(|data| {
+                let data = PriceData::decode(&data).map_err(|e| e.to_string())?;
+                Ok(FixedPrice::new(data.price, data.expo))
+            });
+        let consensus = self.simulate_consensus(oracle_account, &price).await?;
+
+        let reconciliation = Reconciliation {
+            symbol: config.symbol.clone(),
+            backend_price: price.price,
+            onchain_pyth_price: pyth.as_ref().ok().copied(),
+            pyth_divergence_bps: pyth.as_ref().ok().and_then(|onchain| divergence_bps(*onchain, price.price)),
+            pyth_error: pyth.err(),
+            onchain_consensus_price: consensus.as_ref().ok().copied(),
+            consensus_divergence_bps: consensus.as_ref().ok().and_then(|onchain| divergence_bps(*onchain, price.price)),
+            consensus_error: consensus.err(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        if let Some(bps) = reconciliation.pyth_divergence_bps {
+            self.metrics.record_divergence(&config.symbol, "pyth", bps);
+        }
+        if let Some(bps) = reconciliation.consensus_divergence_bps {
+            self.metrics.record_divergence(&config.symbol, "consensus", bps);
+        }
+
+        let diverged = reconciliation.diverged(self.alert_bps);
+        if diverged {
+            warn!("On-chain divergence for {}: {:?}", config.symbol, reconciliation);
+        } else {
+            info!("Reconciled {} with the oracle program", config.symbol);
+        }
+        // Divergences alert through the engine, which deduplicates them across checks
+        if let Some(alerts) = &self.alerts {
+            alerts.observe_divergence(&config.symbol, diverged.then(|| reconciliation.describe(self.alert_bps)));
+        }
+        self.database.save_reconciliation(&reconciliation).await
+    }
+
+    // validate_price_consensus over the sources the backend used, at the exponent the program returns
+    async fn simulate_consensus(&self, oracle_account: Pubkey, price: &ConsensusPrice) -> Result<Result<FixedPrice, String>> {
+        let prices = price
+            .sources
+            .iter()
+            .map(|source| {
+                let tag = match source.source.as_str() {
+                    "Pyth" => PriceSource::Pyth,
+                    "Switchboard" => PriceSource::Switchboard,
+                    _ => PriceSource::Internal,
+                };
+                PriceData::from_fixed(source.price, source.confidence, price.timestamp - source.age_secs, tag)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let expo = prices.iter().map(|p| p.expo).min().unwrap_or(0);
+
+        let result = self
+            .simulate(oracle_program::validate_price_consensus(self.program_id, oracle_account, &prices))
+            .await?;
+        Ok(result.and_then(|data| {
+            let median = oracle_program::return_data::<8>(&data).map_err(|e| e.to_string())?;
+            Ok(FixedPrice::new(i64::from_le_bytes(median), expo))
+        }))
+    }
+
+    // Outer error: the simulation could not be run. Inner error: the program rejected the instruction.
+    async fn simulate(&self, instruction: Instruction) -> Result<Result<Vec<u8>, String>> {
+        let transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(&self.fee_payer)));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.rpc_pool.commitment()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+
+        let result = self
+            .rpc_pool
+            .execute(|client| {
+                let transaction = transaction.clone();
+                let config = config.clone();
+                async move { client.simulate_transaction_with_config(&transaction, config).await }
+            })
+            .await?
+            .value;
+
+        if let Some(err) = result.err {
+            // Anchor logs the named error code; prefer it over the bare custom error number
+            let logs = result.logs.unwrap_or_default();
+            let anchor_error = logs.iter().find(|line| line.contains("Error Code:"));
+            return Ok(Err(anchor_error.cloned().unwrap_or_else(|| err.to_string())));
+        }
+
+        let Some(return_data) = result.return_data else {
+            return Ok(Ok(Vec::new()));
+        };
+        base64::engine::general_purpose::STANDARD
+            .decode(&return_data.data.0)
+            .map(Ok)
+            .map_err(|e| anyhow!("Invalid return data: {}", e))
+    }
+}
+
+fn divergence_bps(onchain: FixedPrice, backend: FixedPrice) -> Option<u64> {
+    let expo = consensus::common_expo(&[onchain, backend]);
+    let onchain = consensus::mantissa_at(&onchain, expo).ok()?;
+    let backend = consensus::mantissa_at(&backend, expo).ok()?;
+    u64::try_from(consensus::deviation_bps(onchain, backend)).ok()
+}
@@ -0,0 +1,235 @@
+use solana_client::client_error::Result as ClientResult;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use anyhow::{Result, anyhow};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use log::warn;
+
+// Weight given to the latest sample in the latency / error-rate moving averages
+const EWMA_ALPHA: f64 = 0.2;
+// Each slot behind the freshest endpoint costs roughly one slot time of latency
+const SLOT_LAG_PENALTY_MS: f64 = 400.0;
+
+#[derive(Default)]
+struct EndpointHealth {
+    latency_ms: f64,
+    error_rate: f64,
+    slot: u64,
+}
+
+// Point-in-time health of one endpoint, for metrics and health checks
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EndpointStatus {
+    // Position in SOLANA_RPC_URLS; stays distinct when several endpoints share a host
+    pub index: usize,
+    // Host only, so API keys embedded in provider URLs are not exposed
+    pub endpoint: String,
+    pub latency_ms: f64,
+    pub error_rate: f64,
+    pub slot: u64,
+    pub slot_lag: u64,
+}
+
+struct RpcEndpoint {
+    url: String,
+    client: Arc<RpcClient>,
+    health: Mutex<EndpointHealth>,
+}
+
+impl RpcEndpoint {
+    fn record_success(&self, elapsed: Duration) {
+        let mut health = self.health.lock().unwrap();
+        let latency_ms = elapsed.as_secs_f64() * 1000.0;
+        health.latency_ms = if health.latency_ms == 0.0 {
+            latency_ms
+        } else {
+            health.latency_ms * (1.0 - EWMA_ALPHA) + latency_ms * EWMA_ALPHA
+        };
+        health.error_rate *= 1.0 - EWMA_ALPHA;
+    }
+
+    fn record_error(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.error_rate = health.error_rate * (1.0 - EWMA_ALPHA) + EWMA_ALPHA;
+    }
+
+    // Lower is healthier. Errors add their expected cost in milliseconds rather than scaling latency,
+    // so an endpoint that has never answered (zero latency) still ranks below one that has.
+    fn score(&self, max_slot: u64, error_penalty_ms: f64) -> f64 {
+        let health = self.health.lock().unwrap();
+        let slot_lag = max_slot.saturating_sub(health.slot) as f64;
+        health.latency_ms + health.error_rate * error_penalty_ms + slot_lag * SLOT_LAG_PENALTY_MS
+    }
+}
+
+// Spreads RPC traffic over several providers, preferring the healthiest and failing over on errors
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    commitment: CommitmentConfig,
+    max_slot: AtomicU64,
+    // A failed request can cost up to a full timeout before the pool fails over
+    error_penalty_ms: f64,
+}
+
+impl RpcPool {
+    pub fn new(urls: Vec<String>, timeout: Duration, commitment: CommitmentConfig) -> Result<Self> {
+        if urls.is_empty() {
+            return Err(anyhow!("RPC pool needs at least one endpoint"));
+        }
+
+        let endpoints = urls
+            .into_iter()
+            .map(|url| RpcEndpoint {
+                client: Arc::new(RpcClient::new_with_timeout_and_commitment(url.clone(), timeout, commitment)),
+                url,
+                health: Mutex::new(EndpointHealth::default()),
+            })
+            .collect();
+
+        Ok(Self {
+            endpoints,
+            commitment,
+            max_slot: AtomicU64::new(0),
+            error_penalty_ms: timeout.as_secs_f64() * 1000.0,
+        })
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    pub fn primary_url(&self) -> &str {
+        &self.endpoints[0].url
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let max_slot = self.max_slot.load(Ordering::SeqCst);
+        self.endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let health = endpoint.health.lock().unwrap();
+                EndpointStatus {
+                    index,
+                    endpoint: reqwest::Url::parse(&endpoint.url)
+                        .ok()
+                        .and_then(|url| url.host_str().map(str::to_string))
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    latency_ms: health.latency_ms,
+                    error_rate: health.error_rate,
+                    slot: health.slot,
+                    slot_lag: max_slot.saturating_sub(health.slot),
+                }
+            })
+            .collect()
+    }
+
+    // Runs `op` against endpoints in health order until one succeeds
+    pub async fn execute<T, F, Fut>(&self, op: F) -> Result<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut last_error = None;
+
+        for endpoint in self.ranked() {
+            let started = Instant::now();
+            match op(endpoint.client.clone()).await {
+                Ok(value) => {
+                    endpoint.record_success(started.elapsed());
+                    return Ok(value);
+                }
+                Err(e) => {
+                    warn!("RPC request to {} failed, failing over: {}", endpoint.url, e);
+                    endpoint.record_error();
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.map(anyhow::Error::from).unwrap_or_else(|| anyhow!("No RPC endpoints available")))
+    }
+
+    // Polls every endpoint's slot so lagging providers drop down the ranking
+    pub async fn run_health_checks(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+            self.refresh_slots().await;
+        }
+    }
+
+    pub async fn refresh_slots(&self) {
+        let slots = futures::future::join_all(self.endpoints.iter().map(|endpoint| async move {
+            let started = Instant::now();
+            let slot = endpoint.client.get_slot().await;
+            (endpoint, slot, started.elapsed())
+        }))
+        .await;
+
+        for (endpoint, slot, elapsed) in slots {
+            match slot {
+                Ok(slot) => {
+                    endpoint.record_success(elapsed);
+                    endpoint.health.lock().unwrap().slot = slot;
+                    self.max_slot.fetch_max(slot, Ordering::SeqCst);
+                }
+                Err(e) => {
+                    warn!("Health check for {} failed: {}", endpoint.url, e);
+                    endpoint.record_error();
+                }
+            }
+        }
+    }
+
+    fn ranked(&self) -> Vec<&RpcEndpoint> {
+        let max_slot = self.max_slot.load(Ordering::SeqCst);
+        let mut ranked: Vec<(f64, &RpcEndpoint)> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| (endpoint.score(max_slot, self.error_penalty_ms), endpoint))
+            .collect();
+
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ranked.into_iter().map(|(_, endpoint)| endpoint).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> RpcPool {
+        let urls = vec!["http://first.example".to_string(), "http://second.example".to_string()];
+        RpcPool::new(urls, Duration::from_secs(5), CommitmentConfig::confirmed()).unwrap()
+    }
+
+    fn ranked_urls(pool: &RpcPool) -> Vec<&str> {
+        pool.ranked().into_iter().map(|endpoint| endpoint.url.as_str()).collect()
+    }
+
+    #[test]
+    fn failing_endpoint_without_latency_ranks_below_a_slow_healthy_one() {
+        let pool = pool();
+        for _ in 0..5 {
+            pool.endpoints[0].record_error();
+        }
+        pool.endpoints[1].record_success(Duration::from_millis(800));
+
+        assert_eq!(ranked_urls(&pool), ["http://second.example", "http://first.example"]);
+    }
+
+    #[test]
+    fn occasional_errors_do_not_outweigh_a_large_latency_gap() {
+        let pool = pool();
+        pool.endpoints[0].record_success(Duration::from_millis(50));
+        pool.endpoints[0].record_error();
+        pool.endpoints[1].record_success(Duration::from_millis(2_000));
+
+        assert_eq!(ranked_urls(&pool), ["http://first.example", "http://second.example"]);
+    }
+}
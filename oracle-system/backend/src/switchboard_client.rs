@@ -1,33 +1,21 @@
-use solana_client::rpc_client::RpcClient;
+use solana_sdk::account::Account;
 use solana_sdk::pubkey::Pubkey;
 use anyhow::Result;
-use std::sync::Arc;
+use oracle_core::FixedPrice;
 
 // Placeholder for Switchboard client logic
 // Real implementation would use switchboard-v2 or v3 crates
-pub struct SwitchboardClient {
-    rpc_client: Arc<RpcClient>,
-}
+#[derive(Default)]
+pub struct SwitchboardClient;
 
 impl SwitchboardClient {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
-        Self {
-            rpc_client,
-        }
-    }
-
-    pub async fn get_price(&self, aggregator_pubkey: &Pubkey) -> Result<f64> {
-        // Simulate fetching data
-        // In reality: deserialize AggregatorAccountData
-        let _client = self.rpc_client.clone();
-        let _key = *aggregator_pubkey;
-
-        // Mock response
-        Ok(100.0) 
+    pub fn new() -> Self {
+        Self
     }
 
-    pub async fn get_price_data(&self, aggregator_pubkey: &Pubkey) -> Result<(f64, f64, i64)> {
+    pub fn get_price_data(&self, _aggregator_pubkey: &Pubkey, _account: &Account) -> Result<(FixedPrice, FixedPrice, i64)> {
+        // In reality: deserialize AggregatorAccountData from the account data
         // Mock response: price, confidence, timestamp
-        Ok((100.0, 0.1, chrono::Utc::now().timestamp()))
+        Ok((FixedPrice::new(100_000_000, -6), FixedPrice::new(100_000, -6), chrono::Utc::now().timestamp()))
     }
 }
@@ -0,0 +1,483 @@
+use crate::database::Database;
+use crate::oracle_manager::{OracleConfig, OracleManager, Schedule};
+use crate::price_aggregator::{AggregationStrategy, QuorumPolicy, Thresholds};
+use serde::{Serialize, Deserialize};
+use anyhow::{Result, anyhow, bail};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use log::{info, error};
+
+// Source names update_price reports prices under
+pub const KNOWN_SOURCES: [&str; 2] = ["Pyth", "Switchboard"];
+
+#[derive(Debug, Deserialize)]
+struct SymbolFile {
+    #[serde(default)]
+    symbols: Vec<SymbolSpec>,
+}
+
+// One [[symbols]] table, and the body of admin API updates; feeds are kept as strings here so
+// errors can name the symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SymbolSpec {
+    pub symbol: String,
+    pub pyth_feed: String,
+    // Hex Pyth price feed ID, required to read the symbol through Hermes
+    pub pyth_feed_id: Option<String>,
+    pub switchboard_feed: String,
+    // Oracle program config account to publish to; the keeper skips symbols without one
+    pub oracle_account: Option<String>,
+    #[serde(default)]
+    pub aggregation: AggregationStrategy,
+    #[serde(default)]
+    pub quorum: QuorumPolicy,
+    #[serde(default)]
+    pub thresholds: Thresholds,
+    #[serde(default)]
+    pub schedule: Schedule,
+}
+
+// Admin API changes to a symbol, persisted so they survive restarts and layered over the file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolOverride {
+    pub symbol: String,
+    // Replaces the file's config for the symbol when set
+    pub config: Option<SymbolSpec>,
+    pub enabled: bool,
+    // Hides a symbol the file still defines
+    pub removed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolListing {
+    pub symbol: String,
+    pub config: SymbolSpec,
+    pub enabled: bool,
+    // "file" or "admin", whichever layer the config came from
+    pub origin: &'static str,
+}
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("{0}")]
+    Invalid(anyhow::Error),
+    #[error("Unknown symbol {0}")]
+    UnknownSymbol(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+// Parses and validates a TOML symbol file, keyed by symbol
+pub fn parse(contents: &str) -> Result<HashMap<String, OracleConfig>> {
+    let file: SymbolFile = toml::from_str(contents)?;
+
+    let mut configs = HashMap::new();
+    for entry in file.symbols {
+        let config = entry.to_config()?;
+        if configs.contains_key(&config.symbol) {
+            bail!("Symbol {} is configured more than once", config.symbol);
+        }
+        configs.insert(config.symbol.clone(), config);
+    }
+    Ok(configs)
+}
+
+pub fn load(path: &PathBuf) -> Result<HashMap<String, OracleConfig>> {
+    let contents = std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    parse(&contents).map_err(|e| anyhow!("Invalid symbol config {}: {}", path.display(), e))
+}
+
+// Re-reads the file every interval and applies it when it changes; an invalid edit is logged and the
+// running configs are kept
+pub async fn watch(path: PathBuf, registry: Arc<SymbolRegistry>, interval: Duration) {
+    let mut last = std::fs::read_to_string(&path).ok();
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("Failed to read symbol config {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if last.as_deref() == Some(contents.as_str()) {
+            continue;
+        }
+        last = Some(contents.clone());
+
+        match parse(&contents) {
+            Ok(configs) => {
+                info!("Reloaded {} symbols from {}", configs.len(), path.display());
+                registry.set_file_configs(configs).await;
+            }
+            Err(e) => error!("Ignoring invalid symbol config {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl SymbolSpec {
+    pub fn to_config(&self) -> Result<OracleConfig> {
+        let symbol = self.symbol.trim().to_string();
+        // Matches the VARCHAR(20) symbol columns in Postgres
+        if symbol.is_empty() || symbol.len() > 20 {
+            bail!("Symbol name must be 1 to 20 characters, got {:?}", symbol);
+        }
+
+        let pubkey = |field: &str, value: &str| {
+            Pubkey::from_str(value).map_err(|e| anyhow!("{}: invalid {} {:?}: {}", symbol, field, value, e))
+        };
+        let pyth_feed = pubkey("pyth_feed", &self.pyth_feed)?;
+        let switchboard_feed = pubkey("switchboard_feed", &self.switchboard_feed)?;
+        let pyth_feed_id = self
+            .pyth_feed_id
+            .as_ref()
+            .map(|id| {
+                hex::decode(id.trim_start_matches("0x"))
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| anyhow!("{}: pyth_feed_id must be 32 hex-encoded bytes", symbol))
+            })
+            .transpose()?;
+        let oracle_account = self
+            .oracle_account
+            .as_ref()
+            .map(|account| pubkey("oracle_account", account))
+            .transpose()?;
+
+        validate_sources(&symbol, "quorum.required_sources", &self.quorum.required_sources)?;
+        if self.quorum.min_sources == 0 || self.quorum.min_sources > KNOWN_SOURCES.len() {
+            bail!("{}: quorum.min_sources must be between 1 and {}", symbol, KNOWN_SOURCES.len());
+        }
+
+        match &self.aggregation {
+            AggregationStrategy::TrimmedMean { trim_pct } if *trim_pct >= 50 => {
+                bail!("{}: trimmed_mean trim_pct must be below 50", symbol)
+            }
+            AggregationStrategy::SourcePriority { order } => validate_sources(&symbol, "aggregation.order", order)?,
+            _ => {}
+        }
+
+        let thresholds = &self.thresholds;
+        let sources: Vec<String> = thresholds.sources.keys().cloned().collect();
+        validate_sources(&symbol, "thresholds.sources", &sources)?;
+        let staleness = thresholds.sources.values().filter_map(|s| s.max_staleness);
+        if staleness.chain([thresholds.max_staleness]).any(|secs| secs <= 0) {
+            bail!("{}: max_staleness must be positive", symbol);
+        }
+
+        // A zero period would panic in tokio::time::interval
+        if self.schedule.interval_ms == 0 || self.schedule.fetch_timeout_ms == 0 {
+            bail!("{}: schedule.interval_ms and schedule.fetch_timeout_ms must be positive", symbol);
+        }
+
+        Ok(OracleConfig {
+            symbol,
+            pyth_feed,
+            pyth_feed_id,
+            switchboard_feed,
+            oracle_account,
+            aggregation: self.aggregation.clone(),
+            quorum: self.quorum.clone(),
+            thresholds: self.thresholds.clone(),
+            schedule: self.schedule.clone(),
+        })
+    }
+}
+
+impl From<&OracleConfig> for SymbolSpec {
+    fn from(config: &OracleConfig) -> Self {
+        Self {
+            symbol: config.symbol.clone(),
+            pyth_feed: config.pyth_feed.to_string(),
+            pyth_feed_id: config.pyth_feed_id.map(hex::encode),
+            switchboard_feed: config.switchboard_feed.to_string(),
+            oracle_account: config.oracle_account.map(|account| account.to_string()),
+            aggregation: config.aggregation.clone(),
+            quorum: config.quorum.clone(),
+            thresholds: config.thresholds.clone(),
+            schedule: config.schedule.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Layers {
+    file: HashMap<String, OracleConfig>,
+    overrides: HashMap<String, SymbolOverride>,
+}
+
+impl Layers {
+    // Effective config per symbol with whether it is enabled and which layer it came from
+    fn resolve(&self) -> Vec<(OracleConfig, bool, &'static str)> {
+        let symbols: BTreeSet<&String> = self.file.keys().chain(self.overrides.keys()).collect();
+        symbols
+            .into_iter()
+            .filter_map(|symbol| {
+                let overrides = self.overrides.get(symbol);
+                if overrides.is_some_and(|o| o.removed) {
+                    return None;
+                }
+
+                let enabled = overrides.is_none_or(|o| o.enabled);
+                match overrides.and_then(|o| o.config.as_ref()) {
+                    // Specs are validated before they are stored, so this only fails on a hand-edited row
+                    Some(spec) => match spec.to_config() {
+                        Ok(config) => Some((config, enabled, "admin")),
+                        Err(e) => {
+                            error!("Ignoring stored config for {}: {}", symbol, e);
+                            None
+                        }
+                    },
+                    None => self.file.get(symbol).map(|config| (config.clone(), enabled, "file")),
+                }
+            })
+            .collect()
+    }
+}
+
+// Combines the symbol file with admin API overrides and keeps OracleManager running the result
+pub struct SymbolRegistry {
+    manager: Arc<OracleManager>,
+    database: Arc<Database>,
+    layers: Mutex<Layers>,
+}
+
+impl SymbolRegistry {
+    pub fn new(manager: Arc<OracleManager>, database: Arc<Database>) -> Self {
+        Self {
+            manager,
+            database,
+            layers: Mutex::new(Layers::default()),
+        }
+    }
+
+    // Startup: the validated file plus every override persisted by earlier admin changes
+    pub async fn load(&self, file: HashMap<String, OracleConfig>) -> Result<()> {
+        let overrides = self.database.load_symbol_overrides().await?;
+        let mut layers = self.layers.lock().await;
+        layers.file = file;
+        layers.overrides = overrides.into_iter().map(|o| (o.symbol.clone(), o)).collect();
+        self.apply(&layers);
+        Ok(())
+    }
+
+    pub async fn set_file_configs(&self, file: HashMap<String, OracleConfig>) {
+        let mut layers = self.layers.lock().await;
+        layers.file = file;
+        self.apply(&layers);
+    }
+
+    pub async fn list(&self) -> Vec<SymbolListing> {
+        let layers = self.layers.lock().await;
+        layers
+            .resolve()
+            .into_iter()
+            .map(|(config, enabled, origin)| SymbolListing {
+                symbol: config.symbol.clone(),
+                config: SymbolSpec::from(&config),
+                enabled,
+                origin,
+            })
+            .collect()
+    }
+
+    // Adds the symbol or replaces its config
+    pub async fn upsert(&self, spec: SymbolSpec) -> Result<(), RegistryError> {
+        let config = spec.to_config().map_err(RegistryError::Invalid)?;
+        let mut layers = self.layers.lock().await;
+        let enabled = layers.overrides.get(&config.symbol).is_none_or(|o| o.enabled || o.removed);
+        let overrides = SymbolOverride {
+            symbol: config.symbol.clone(),
+            config: Some(SymbolSpec::from(&config)),
+            enabled,
+            removed: false,
+        };
+        self.store(&mut layers, overrides).await
+    }
+
+    pub async fn remove(&self, symbol: &str) -> Result<(), RegistryError> {
+        let mut layers = self.layers.lock().await;
+        let overrides = self.existing(&layers, symbol)?;
+        self.store(&mut layers, SymbolOverride { config: None, removed: true, ..overrides }).await
+    }
+
+    pub async fn set_enabled(&self, symbol: &str, enabled: bool) -> Result<(), RegistryError> {
+        let mut layers = self.layers.lock().await;
+        let overrides = self.existing(&layers, symbol)?;
+        self.store(&mut layers, SymbolOverride { enabled, ..overrides }).await
+    }
+
+    // Current override for a symbol that is live in either layer
+    fn existing(&self, layers: &Layers, symbol: &str) -> Result<SymbolOverride, RegistryError> {
+        if !layers.resolve().iter().any(|(config, _, _)| config.symbol == symbol) {
+            return Err(RegistryError::UnknownSymbol(symbol.to_string()));
+        }
+        Ok(layers.overrides.get(symbol).cloned().unwrap_or_else(|| SymbolOverride {
+            symbol: symbol.to_string(),
+            config: None,
+            enabled: true,
+            removed: false,
+        }))
+    }
+
+    // Persists before applying so a failed write never leaves a change that a restart would undo
+    async fn store(&self, layers: &mut Layers, overrides: SymbolOverride) -> Result<(), RegistryError> {
+        self.database.save_symbol_override(&overrides).await?;
+        info!("Admin change to {}: enabled={} removed={}", overrides.symbol, overrides.enabled, overrides.removed);
+        layers.overrides.insert(overrides.symbol.clone(), overrides);
+        self.apply(layers);
+        Ok(())
+    }
+
+    fn apply(&self, layers: &Layers) {
+        let configs = layers
+            .resolve()
+            .into_iter()
+            .filter(|(_, enabled, _)| *enabled)
+            .map(|(config, _, _)| (config.symbol.clone(), config))
+            .collect();
+        self.manager.replace_configs(configs);
+    }
+}
+
+fn validate_sources(symbol: &str, field: &str, sources: &[String]) -> Result<()> {
+    match sources.iter().find(|source| !KNOWN_SOURCES.contains(&source.as_str())) {
+        Some(unknown) => bail!("{}: unknown source {:?} in {} (expected one of {:?})", symbol, unknown, field, KNOWN_SOURCES),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYMBOL: &str = r#"
+[[symbols]]
+symbol = "SOL"
+pyth_feed = "J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix"
+switchboard_feed = "GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR"
+"#;
+
+    fn parse_with(extra: &str) -> Result<HashMap<String, OracleConfig>> {
+        parse(&format!("{}{}", SYMBOL, extra))
+    }
+
+    #[test]
+    fn shipped_symbol_file_parses() {
+        parse(include_str!("../symbols.toml")).unwrap();
+    }
+
+    #[test]
+    fn omitted_tables_use_defaults() {
+        let configs = parse_with("").unwrap();
+        assert_eq!(configs["SOL"].thresholds, Thresholds::default());
+        assert_eq!(configs["SOL"].quorum, QuorumPolicy::default());
+        assert_eq!(configs["SOL"].schedule, Schedule::default());
+    }
+
+    #[test]
+    fn misspelled_keys_are_rejected() {
+        let typos = [
+            "[symbols.thresholds]\nmax_stalenes = 60\n",
+            "[symbols.thresholds.sources.Pyth]\nmax_confidence = 50\n",
+            "[symbols.quorum]\nmin_source = 2\n",
+            "[symbols.schedule]\ninterval = 1000\n",
+            "aggregation = { type = \"trimmed_mean\", trim_pct = 10, trim = 5 }\n",
+        ];
+        for typo in typos {
+            assert!(parse_with(typo).is_err(), "accepted {:?}", typo);
+        }
+    }
+
+    #[test]
+    fn aggregation_strategies_parse() {
+        let configs = parse_with("aggregation = { type = \"source_priority\", order = [\"Pyth\"] }\n").unwrap();
+        assert_eq!(configs["SOL"].aggregation, AggregationStrategy::SourcePriority { order: vec!["Pyth".to_string()] });
+        let configs = parse_with("aggregation = { type = \"median\" }\n").unwrap();
+        assert_eq!(configs["SOL"].aggregation, AggregationStrategy::Median);
+    }
+
+    // The file's configs for `symbols`, each with the given interval
+    fn file(symbols: &[&str], interval_ms: u64) -> HashMap<String, OracleConfig> {
+        let entries: String = symbols
+            .iter()
+            .map(|symbol| SYMBOL.replace("\"SOL\"", &format!("{:?}", symbol)) + &format!("[symbols.schedule]\ninterval_ms = {}\n", interval_ms))
+            .collect();
+        parse(&entries).unwrap()
+    }
+
+    fn admin_override(symbol: &str, config: Option<&OracleConfig>, enabled: bool, removed: bool) -> SymbolOverride {
+        SymbolOverride { symbol: symbol.to_string(), config: config.map(SymbolSpec::from), enabled, removed }
+    }
+
+    fn resolved(layers: &Layers) -> Vec<(String, u64, bool, &'static str)> {
+        layers
+            .resolve()
+            .into_iter()
+            .map(|(config, enabled, origin)| (config.symbol, config.schedule.interval_ms, enabled, origin))
+            .collect()
+    }
+
+    #[test]
+    fn overrides_layer_on_top_of_the_file() {
+        let mut admin_sol = file(&["SOL"], 500)["SOL"].clone();
+        admin_sol.schedule.interval_ms = 1_000;
+        let admin_ada = file(&["ADA"], 250)["ADA"].clone();
+
+        let layers = Layers {
+            file: file(&["BTC", "ETH", "SOL", "XRP"], 500),
+            overrides: HashMap::from([
+                ("SOL".to_string(), admin_override("SOL", Some(&admin_sol), true, false)),
+                ("ETH".to_string(), admin_override("ETH", None, false, false)),
+                ("XRP".to_string(), admin_override("XRP", None, true, true)),
+                ("ADA".to_string(), admin_override("ADA", Some(&admin_ada), true, false)),
+            ]),
+        };
+
+        assert_eq!(
+            resolved(&layers),
+            vec![
+                ("ADA".to_string(), 250, true, "admin"),
+                ("BTC".to_string(), 500, true, "file"),
+                ("ETH".to_string(), 500, false, "file"),
+                ("SOL".to_string(), 1_000, true, "admin"),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_reloads_keep_the_overrides() {
+        let mut admin_sol = file(&["SOL"], 500)["SOL"].clone();
+        admin_sol.schedule.interval_ms = 1_000;
+        let mut layers = Layers {
+            file: file(&["ETH", "SOL", "XRP"], 500),
+            overrides: HashMap::from([
+                ("SOL".to_string(), admin_override("SOL", Some(&admin_sol), true, false)),
+                ("ETH".to_string(), admin_override("ETH", None, false, false)),
+                ("XRP".to_string(), admin_override("XRP", None, true, true)),
+            ]),
+        };
+
+        // The reload changes every file config and adds BTC, as SymbolRegistry::set_file_configs does
+        layers.file = file(&["BTC", "ETH", "SOL", "XRP"], 2_000);
+
+        assert_eq!(
+            resolved(&layers),
+            vec![
+                ("BTC".to_string(), 2_000, true, "file"),
+                ("ETH".to_string(), 2_000, false, "file"),
+                ("SOL".to_string(), 1_000, true, "admin"),
+            ]
+        );
+    }
+}
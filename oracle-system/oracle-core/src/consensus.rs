@@ -0,0 +1,276 @@
+use alloc::vec::Vec;
+use core::fmt;
+use crate::price::FixedPrice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsensusError {
+    NoPrices,
+    DeviationTooHigh,
+    NoPricesWithinDeviation,
+    // A value cannot be represented at the common exponent
+    Overflow,
+}
+
+impl fmt::Display for ConsensusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsensusError::NoPrices => f.write_str("No prices to aggregate"),
+            ConsensusError::DeviationTooHigh => f.write_str("Price deviation exceeds threshold"),
+            ConsensusError::NoPricesWithinDeviation => f.write_str("No prices within deviation threshold"),
+            ConsensusError::Overflow => f.write_str("Price cannot be expressed at the common exponent"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConsensusError {}
+
+// Median of a set of prices and, per input, whether it sits within the deviation threshold
+#[derive(Debug, Clone)]
+pub struct Agreement {
+    // Finest exponent among the inputs; median and deviations are expressed at it
+    pub expo: i32,
+    pub median: i128,
+    pub deviation_bps: Vec<u128>,
+    pub within: Vec<bool>,
+}
+
+impl Agreement {
+    pub fn median_price(&self) -> Result<FixedPrice, ConsensusError> {
+        to_fixed(self.median, self.expo)
+    }
+}
+
+pub fn common_expo(prices: &[FixedPrice]) -> i32 {
+    prices.iter().map(|p| p.expo).min().unwrap_or(0)
+}
+
+pub fn mantissa_at(price: &FixedPrice, expo: i32) -> Result<i128, ConsensusError> {
+    price.mantissa_at(expo).ok_or(ConsensusError::Overflow)
+}
+
+pub fn to_fixed(mantissa: i128, expo: i32) -> Result<FixedPrice, ConsensusError> {
+    let mantissa = i64::try_from(mantissa).map_err(|_| ConsensusError::Overflow)?;
+    Ok(FixedPrice::new(mantissa, expo))
+}
+
+// Upper median for odd counts, truncated midpoint of the middle pair for even counts
+pub fn median(sorted: &[i128]) -> Option<i128> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        Some((sorted[mid - 1] + sorted[mid]) / 2)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+// |value - reference| in basis points of |reference|; zero when the reference is zero
+pub fn deviation_bps(value: i128, reference: i128) -> u128 {
+    (value - reference)
+        .unsigned_abs()
+        .saturating_mul(10_000)
+        .checked_div(reference.unsigned_abs())
+        .unwrap_or(0)
+}
+
+// Confidence interval width in basis points of |price|, as the contract's max_confidence check computes it
+pub fn confidence_bps(price: &FixedPrice, confidence: &FixedPrice) -> Result<u128, ConsensusError> {
+    let expo = common_expo(&[*price, *confidence]);
+    let price = mantissa_at(price, expo)?;
+    let confidence = mantissa_at(confidence, expo)?;
+    Ok(deviation_bps(price + confidence.abs(), price))
+}
+
+pub fn agreement(prices: &[FixedPrice], max_deviation_bps: u64) -> Result<Agreement, ConsensusError> {
+    if prices.is_empty() {
+        return Err(ConsensusError::NoPrices);
+    }
+
+    let expo = common_expo(prices);
+    let mantissas = prices
+        .iter()
+        .map(|p| mantissa_at(p, expo))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut sorted = mantissas.clone();
+    sorted.sort_unstable();
+    let median = median(&sorted).ok_or(ConsensusError::NoPrices)?;
+
+    let deviation_bps: Vec<u128> = mantissas.iter().map(|m| deviation_bps(*m, median)).collect();
+    let within = deviation_bps.iter().map(|d| *d <= max_deviation_bps as u128).collect();
+
+    Ok(Agreement { expo, median, deviation_bps, within })
+}
+
+// Scale for sigma multipliers, so 2_000 means intervals of price ± 2 * confidence
+pub const SIGMA_SCALE: u64 = 1_000;
+
+// Whether each price's interval, price ± confidence * sigma_milli / SIGMA_SCALE, overlaps the intervals
+// of a strict majority of the inputs (itself included)
+pub fn interval_overlap(prices: &[(FixedPrice, FixedPrice)], sigma_milli: u64) -> Result<Vec<bool>, ConsensusError> {
+    if prices.is_empty() {
+        return Err(ConsensusError::NoPrices);
+    }
+
+    let all: Vec<FixedPrice> = prices.iter().flat_map(|(price, conf)| [*price, *conf]).collect();
+    let expo = common_expo(&all);
+
+    let mut intervals = Vec::with_capacity(prices.len());
+    for (price, conf) in prices {
+        let half_width = mantissa_at(conf, expo)?
+            .abs()
+            .checked_mul(sigma_milli as i128)
+            .ok_or(ConsensusError::Overflow)?
+            / SIGMA_SCALE as i128;
+        intervals.push((mantissa_at(price, expo)?, half_width));
+    }
+
+    Ok(intervals
+        .iter()
+        .map(|(price, half_width)| {
+            let overlapping = intervals
+                .iter()
+                .filter(|(other, other_width)| (price - other).abs() <= half_width + other_width)
+                .count();
+            overlapping * 2 > intervals.len()
+        })
+        .collect())
+}
+
+// On-chain semantics: the median, but only if every price is within the threshold
+pub fn validate_consensus(prices: &[FixedPrice], max_deviation_bps: u64) -> Result<FixedPrice, ConsensusError> {
+    let agreement = agreement(prices, max_deviation_bps)?;
+    if agreement.within.iter().any(|within| !within) {
+        return Err(ConsensusError::DeviationTooHigh);
+    }
+    agreement.median_price()
+}
+
+// Arithmetic mean truncated toward zero, at the finest exponent among the inputs
+pub fn mean(prices: &[FixedPrice]) -> Result<FixedPrice, ConsensusError> {
+    if prices.is_empty() {
+        return Err(ConsensusError::NoPrices);
+    }
+
+    let expo = common_expo(prices);
+    let mut sum = 0i128;
+    for price in prices {
+        sum = sum.checked_add(mantissa_at(price, expo)?).ok_or(ConsensusError::Overflow)?;
+    }
+    to_fixed(sum / prices.len() as i128, expo)
+}
+
+// Median at the finest exponent among the inputs
+pub fn median_price(prices: &[FixedPrice]) -> Result<FixedPrice, ConsensusError> {
+    let expo = common_expo(prices);
+    let mut sorted = prices
+        .iter()
+        .map(|p| mantissa_at(p, expo))
+        .collect::<Result<Vec<_>, _>>()?;
+    sorted.sort_unstable();
+    to_fixed(median(&sorted).ok_or(ConsensusError::NoPrices)?, expo)
+}
+
+// Scale for integer inverse-confidence weights; keeps weight * mantissa well inside i128
+const WEIGHT_SCALE: i128 = 1_000_000_000_000_000_000;
+
+// Mean weighted by 1 / confidence, so tighter sources count more. A zero confidence is treated
+// as the smallest representable interval rather than an infinite weight.
+pub fn confidence_weighted_mean(prices: &[(FixedPrice, FixedPrice)]) -> Result<FixedPrice, ConsensusError> {
+    if prices.is_empty() {
+        return Err(ConsensusError::NoPrices);
+    }
+
+    let all: Vec<FixedPrice> = prices.iter().flat_map(|(price, conf)| [*price, *conf]).collect();
+    let expo = common_expo(&all);
+
+    let mut weighted_sum = 0i128;
+    let mut total_weight = 0i128;
+    for (price, conf) in prices {
+        let weight = WEIGHT_SCALE / mantissa_at(conf, expo)?.abs().max(1);
+        let weighted = mantissa_at(price, expo)?.checked_mul(weight).ok_or(ConsensusError::Overflow)?;
+        weighted_sum = weighted_sum.checked_add(weighted).ok_or(ConsensusError::Overflow)?;
+        total_weight += weight;
+    }
+
+    to_fixed(weighted_sum / total_weight.max(1), expo)
+}
+
+// Mean after dropping trim_pct percent of the prices (rounded down) from each end
+pub fn trimmed_mean(prices: &[FixedPrice], trim_pct: u8) -> Result<FixedPrice, ConsensusError> {
+    let mut sorted = prices.to_vec();
+    sorted.sort();
+
+    let trim = sorted.len() * trim_pct.min(49) as usize / 100;
+    mean(&sorted[trim..sorted.len() - trim])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn p(mantissa: i64, expo: i32) -> FixedPrice {
+        FixedPrice::new(mantissa, expo)
+    }
+
+    #[test]
+    fn median_price_uses_the_finest_exponent() {
+        let median = median_price(&[p(2, 0), p(15, -1), p(125, -2)]).unwrap();
+        assert_eq!((median.mantissa, median.expo), (150, -2));
+    }
+
+    #[test]
+    fn median_price_of_an_even_count_truncates_the_midpoint() {
+        assert_eq!(median_price(&[p(10, 0), p(1, 0), p(4, 0), p(2, 0)]).unwrap(), p(3, 0));
+        assert_eq!(median_price(&[p(1, 0), p(2, 0)]).unwrap(), p(1, 0));
+        assert_eq!(median_price(&[p(-1, 0), p(-2, 0)]).unwrap(), p(-1, 0));
+        assert_eq!(median_price(&[]), Err(ConsensusError::NoPrices));
+    }
+
+    #[test]
+    fn mean_truncates_toward_zero() {
+        assert_eq!(mean(&[p(1, 0), p(2, 0), p(2, 0)]).unwrap(), p(1, 0));
+        assert_eq!(mean(&[p(-1, 0), p(-2, 0), p(-2, 0)]).unwrap(), p(-1, 0));
+        let mixed = mean(&[p(15, -1), p(3, 0)]).unwrap();
+        assert_eq!((mixed.mantissa, mixed.expo), (22, -1));
+        assert_eq!(mean(&[]), Err(ConsensusError::NoPrices));
+    }
+
+    #[test]
+    fn confidence_weighted_mean_favours_tighter_sources() {
+        let weighted = confidence_weighted_mean(&[(p(100, 0), p(1, 0)), (p(200, 0), p(4, 0))]).unwrap();
+        assert_eq!(weighted, p(120, 0));
+        assert_eq!(confidence_weighted_mean(&[]), Err(ConsensusError::NoPrices));
+    }
+
+    #[test]
+    fn confidence_weighted_mean_treats_zero_confidence_as_the_smallest_interval() {
+        // Both confidences count as one unit at the common exponent of -2, so the weights are equal
+        let weighted = confidence_weighted_mean(&[(p(100, 0), p(0, -2)), (p(200, 0), p(1, -2))]).unwrap();
+        assert_eq!((weighted.mantissa, weighted.expo), (15_000, -2));
+    }
+
+    #[test]
+    fn trimmed_mean_rounds_the_trim_count_down() {
+        let prices = vec![p(100, 0), p(3, 0), p(1, 0), p(40, -1), p(2, 0)];
+        // 5 * 19% rounds down to nothing trimmed
+        assert_eq!(trimmed_mean(&prices, 19).unwrap(), p(22, 0));
+        // 5 * 20% trims one from each end: mean of 2, 3 and 4
+        assert_eq!(trimmed_mean(&prices, 20).unwrap(), p(3, 0));
+        // Capped at 49%, which always leaves the middle value
+        assert_eq!(trimmed_mean(&prices, 100).unwrap(), p(3, 0));
+        assert_eq!(trimmed_mean(&[], 10), Err(ConsensusError::NoPrices));
+    }
+
+    #[test]
+    fn validate_consensus_rejects_any_outlier() {
+        let prices = [p(10_000, -2), p(10_050, -2), p(10_200, -2)];
+        assert_eq!(validate_consensus(&prices, 100), Err(ConsensusError::DeviationTooHigh));
+        assert_eq!(validate_consensus(&prices, 200).unwrap(), p(10_050, -2));
+    }
+}
@@ -0,0 +1,13 @@
+// Price types and consensus algorithms shared by the on-chain program and the backend,
+// so the backend can predict exactly what the contract will accept
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod consensus;
+pub mod price;
+
+pub use consensus::ConsensusError;
+pub use price::FixedPrice;
@@ -0,0 +1,201 @@
+use alloc::format;
+use alloc::string::ToString;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+
+// Exact price value as mantissa * 10^expo, the same representation as the contract's PriceData.
+// Serialized as a decimal string so JSON consumers never round through a float.
+// Equality, ordering and hashing compare the value, so 1.50 (150e-2) equals 1.5 (15e-1).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FixedPrice {
+    pub mantissa: i64,
+    pub expo: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePriceError;
+
+impl fmt::Display for ParsePriceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid fixed-point price")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParsePriceError {}
+
+impl FixedPrice {
+    pub fn new(mantissa: i64, expo: i32) -> Self {
+        Self { mantissa, expo }
+    }
+
+    // Mantissa expressed at a smaller (or equal) exponent; None if it would overflow
+    pub fn mantissa_at(&self, expo: i32) -> Option<i128> {
+        let shift = u32::try_from(self.expo.checked_sub(expo)?).ok()?;
+        (self.mantissa as i128).checked_mul(10i128.checked_pow(shift)?)
+    }
+
+    // Lossy conversion for logging and ratios only
+    pub fn to_f64(self) -> f64 {
+        let scale = if self.expo >= 0 {
+            (0..self.expo).fold(1.0, |acc, _| acc * 10.0)
+        } else {
+            (0..-self.expo).fold(1.0, |acc, _| acc / 10.0)
+        };
+        self.mantissa as f64 * scale
+    }
+
+    // The same value with trailing zeros moved into the exponent, so equal values have equal parts
+    fn normalized(self) -> (i64, i32) {
+        if self.mantissa == 0 {
+            return (0, 0);
+        }
+        let (mut mantissa, mut expo) = (self.mantissa, self.expo);
+        while mantissa % 10 == 0 {
+            let Some(next) = expo.checked_add(1) else { break };
+            mantissa /= 10;
+            expo = next;
+        }
+        (mantissa, expo)
+    }
+}
+
+impl PartialEq for FixedPrice {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FixedPrice {}
+
+impl Hash for FixedPrice {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+impl PartialOrd for FixedPrice {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FixedPrice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Zero is zero at any exponent, and 10^gap can overflow even when the mantissa is 0
+        if self.mantissa == 0 || other.mantissa == 0 {
+            return self.mantissa.signum().cmp(&other.mantissa.signum());
+        }
+
+        let expo = self.expo.min(other.expo);
+        match (self.mantissa_at(expo), other.mantissa_at(expo)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // Only a nonzero mantissa shifted 20+ digits overflows, and that value's magnitude then
+            // exceeds any i64 mantissa at the finer exponent, so its sign decides
+            (None, _) if self.mantissa > 0 => Ordering::Greater,
+            (None, _) => Ordering::Less,
+            (_, None) if other.mantissa > 0 => Ordering::Less,
+            (_, None) => Ordering::Greater,
+        }
+    }
+}
+
+impl fmt::Display for FixedPrice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = self.mantissa.unsigned_abs().to_string();
+
+        if self.expo >= 0 {
+            write!(f, "{}{}", sign, digits)?;
+            for _ in 0..self.expo {
+                f.write_str("0")?;
+            }
+            return Ok(());
+        }
+
+        let decimals = self.expo.unsigned_abs() as usize;
+        let padded = format!("{:0>width$}", digits, width = decimals + 1);
+        let (int_part, frac_part) = padded.split_at(padded.len() - decimals);
+        write!(f, "{}{}.{}", sign, int_part, frac_part)
+    }
+}
+
+impl FromStr for FixedPrice {
+    type Err = ParsePriceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (int_part, frac_part) = s.split_once('.').unwrap_or((s, ""));
+        let expo = -i32::try_from(frac_part.len()).map_err(|_| ParsePriceError)?;
+        let mantissa = format!("{}{}", int_part, frac_part)
+            .parse::<i64>()
+            .map_err(|_| ParsePriceError)?;
+        Ok(Self { mantissa, expo })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FixedPrice {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FixedPrice {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = alloc::string::String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn equal_values_at_different_exponents_are_equal() {
+        let a = FixedPrice::new(150, -2);
+        let b = FixedPrice::new(15, -1);
+        assert_eq!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(HashSet::from([a, b]).len(), 1);
+        assert_eq!(FixedPrice::new(0, -8), FixedPrice::new(0, 3));
+        assert_ne!(FixedPrice::new(151, -2), b);
+    }
+
+    #[test]
+    fn orders_across_exponent_gaps_too_wide_for_i128() {
+        let huge = FixedPrice::new(1, 30);
+        let tiny = FixedPrice::new(i64::MAX, -30);
+        assert!(huge > tiny);
+        assert!(FixedPrice::new(-1, 30) < tiny);
+        assert!(tiny < huge);
+        assert!(FixedPrice::new(-5, -30) > FixedPrice::new(-1, 30));
+    }
+
+    #[test]
+    fn zero_compares_by_sign_across_any_exponent_gap() {
+        let zero_coarse = FixedPrice::new(0, 30);
+        let zero_fine = FixedPrice::new(0, -30);
+        assert_eq!(zero_coarse, zero_fine);
+        assert_eq!(zero_coarse.cmp(&zero_fine), Ordering::Equal);
+        assert_eq!(HashSet::from([zero_coarse, zero_fine]).len(), 1);
+
+        assert!(FixedPrice::new(1, -30) > zero_coarse);
+        assert!(FixedPrice::new(-1, -30) < zero_coarse);
+        assert!(zero_fine < FixedPrice::new(1, 30));
+        assert!(zero_fine > FixedPrice::new(-1, 30));
+        assert!(FixedPrice::new(0, i32::MAX) == FixedPrice::new(0, i32::MIN));
+        assert!(FixedPrice::new(0, i32::MAX) < FixedPrice::new(1, i32::MIN));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for s in ["142.35120000", "-0.00000001", "0.5", "42"] {
+            assert_eq!(s.parse::<FixedPrice>().unwrap().to_string(), s);
+        }
+    }
+}
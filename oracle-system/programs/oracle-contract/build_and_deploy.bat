@@ -1,5 +1,7 @@
 @echo off
 cd /d "%~dp0"
+REM The program depends on ..\..\oracle-core; to build in Docker instead, run
+REM "docker compose run --rm contract" from the workspace root.
 echo Building Anchor Program...
 call anchor build
 if %errorlevel% neq 0 (
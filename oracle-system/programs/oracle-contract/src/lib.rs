@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
 use pyth_sdk_solana::load_price_feed_from_account_info;
-use std::cmp::Ordering;
+use oracle_core::consensus::{self, ConsensusError};
+use oracle_core::FixedPrice;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+// How far ahead of the cluster clock a published price's timestamp may be
+const MAX_CLOCK_SKEW_SECS: i64 = 5;
+
 #[program]
 pub mod oracle_contract {
     use super::*;
@@ -84,39 +88,63 @@ pub mod oracle_contract {
             return err!(ErrorCode::NoPricesProvided);
         }
 
-        // Sort prices to find median
-        let mut sorted_prices = prices.clone();
-        sorted_prices.sort_by(|a, b| {
-            // Normalize prices to same exponent for comparison if needed, 
-            // for simplicity assuming same exponent or normalized before passing
-            a.price.cmp(&b.price)
-        });
-
-        let mid = sorted_prices.len() / 2;
-        let median_price = if sorted_prices.len() % 2 == 0 {
-            (sorted_prices[mid - 1].price + sorted_prices[mid].price) / 2
-        } else {
-            sorted_prices[mid].price
-        };
-
-        // Validate deviation
-        for price_data in prices.iter() {
-            let deviation = (price_data.price - median_price).abs();
-            let deviation_bps = (deviation as u128)
-                .checked_mul(10000)
-                .unwrap()
-                .checked_div(median_price.abs() as u128)
-                .unwrap_or(0);
-
-            if deviation_bps > oracle_config.max_deviation as u128 {
-                // Log or reject specific outlier? 
-                // For strict consensus, if any trusted source is too far off, we might want to fail or just exclude it.
-                // Here we fail for safety.
-                return err!(ErrorCode::PriceDeviationTooHigh);
-            }
+        // Median and deviation arithmetic is shared with the backend via oracle-core,
+        // so off-chain predictions match what this instruction accepts
+        let fixed_prices: Vec<FixedPrice> = prices
+            .iter()
+            .map(|p| FixedPrice::new(p.price, p.expo))
+            .collect();
+
+        let median_price = consensus::validate_consensus(&fixed_prices, oracle_config.max_deviation)
+            .map_err(|e| match e {
+                ConsensusError::NoPrices => ErrorCode::NoPricesProvided,
+                ConsensusError::Overflow => ErrorCode::PriceOverflow,
+                // For strict consensus, if any trusted source is too far off we fail for safety
+                ConsensusError::DeviationTooHigh | ConsensusError::NoPricesWithinDeviation => ErrorCode::PriceDeviationTooHigh,
+            })?;
+
+        // Mantissa at the finest exponent among the inputs (unchanged when all share one)
+        Ok(median_price.mantissa)
+    }
+
+    // Stores the backend's consensus price for this config so other programs can read it.
+    // Only the config authority may publish, and never an older or less certain price than the config allows.
+    pub fn publish_price(
+        ctx: Context<PublishPrice>,
+        price: PriceData,
+    ) -> Result<()> {
+        let oracle_config = &ctx.accounts.oracle_config;
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp - price.timestamp > oracle_config.max_staleness {
+            return err!(ErrorCode::StalePrice);
+        }
+        // A future timestamp would pass the staleness check for longer and block later prices as out of order
+        if price.timestamp > clock.unix_timestamp + MAX_CLOCK_SKEW_SECS {
+            return err!(ErrorCode::FutureTimestamp);
         }
 
-        Ok(median_price)
+        let confidence = i64::try_from(price.confidence).map_err(|_| ErrorCode::PriceOverflow)?;
+        let conf_bps = consensus::confidence_bps(
+            &FixedPrice::new(price.price, price.expo),
+            &FixedPrice::new(confidence, price.expo),
+        )
+        .map_err(|_| ErrorCode::PriceOverflow)?;
+        if conf_bps > oracle_config.max_confidence as u128 {
+            return err!(ErrorCode::LowConfidence);
+        }
+
+        let published_price = &mut ctx.accounts.published_price;
+        if price.timestamp < published_price.timestamp {
+            return err!(ErrorCode::OutOfOrderPrice);
+        }
+
+        published_price.price = price.price;
+        published_price.confidence = price.confidence;
+        published_price.expo = price.expo;
+        published_price.timestamp = price.timestamp;
+        published_price.slot = clock.slot;
+        Ok(())
     }
 }
 
@@ -148,6 +176,23 @@ pub struct ValidatePrice<'info> {
     pub oracle_config: Account<'info, OracleConfig>,
 }
 
+#[derive(Accounts)]
+pub struct PublishPrice<'info> {
+    #[account(has_one = authority)]
+    pub oracle_config: Account<'info, OracleConfig>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PublishedPrice::SIZE,
+        seeds = [b"price", oracle_config.key().as_ref()],
+        bump
+    )]
+    pub published_price: Account<'info, PublishedPrice>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[account]
 pub struct OracleConfig {
     pub symbol: String,
@@ -157,6 +202,20 @@ pub struct OracleConfig {
     pub max_deviation: u64,   // basis points
 }
 
+// Latest price published for an OracleConfig, at PDA ["price", oracle_config]
+#[account]
+pub struct PublishedPrice {
+    pub price: i64,
+    pub confidence: u64,
+    pub expo: i32,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+impl PublishedPrice {
+    pub const SIZE: usize = 8 + 8 + 4 + 8 + 8;
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct PriceData {
     pub price: i64,
@@ -185,4 +244,10 @@ pub enum ErrorCode {
     NoPricesProvided,
     #[msg("Price deviation exceeds threshold")]
     PriceDeviationTooHigh,
+    #[msg("Price cannot be normalized to a common exponent")]
+    PriceOverflow,
+    #[msg("Price is older than the one already published")]
+    OutOfOrderPrice,
+    #[msg("Price timestamp is ahead of the cluster clock")]
+    FutureTimestamp,
 }